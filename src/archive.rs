@@ -0,0 +1,554 @@
+//! Portable archive export/import, backing `Repo::export`/`Repo::import_archive`.
+//!
+//! Bundles every chunk in the store, the sqlite db file, and every thumbnail into a single zip
+//! "pod", alongside a `manifest.txt` listing each member's path, SHA-256 digest, and length —
+//! modeled on sisudoc's source-pod export (`ArchiveMember` entries plus digests written into the
+//! zip). `import` re-hashes each member it extracts just before writing that member, and stops
+//! at the first digest that doesn't match; members written before the mismatch stay in the repo.
+//!
+//! Entries are streamed one at a time through `zip::ZipWriter`/`io::copy` rather than collecting
+//! the whole repo into memory first, since a store can be arbitrarily large.
+//!
+//! The db member is read and restored as the raw `<repo>/vorg.db` file rather than through the
+//! pluggable `Database` trait: unlike chunks (always reachable via `Store`) and thumbnails (always
+//! local files, see `thumbnail`), there is no abstracted way to get raw bytes back out of e.g.
+//! `PostgresDatabase`. Exporting or importing into a repo backed by a non-file `Database` isn't
+//! supported yet; `Repo::export`/`Repo::import_archive` simply skip the db member in that case.
+
+use crate::{
+    error::{Error, ErrorKind, Result},
+    store::Store,
+    thumbnail,
+    utils::{self, ITEM_HASH_HEX_LEN},
+};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::Path,
+};
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+/// Path inside the archive, of the bundled `vorg.db` file.
+const DB_MEMBER_PATH: &str = "db/vorg.db";
+/// Name of the manifest entry, always written last so every other member's digest is already
+/// known by the time it's written.
+const MANIFEST_MEMBER_PATH: &str = "manifest.txt";
+
+/// One file bundled into an archive: its path inside the zip, content digest, and length.
+struct ArchiveMember {
+    path: String,
+    sha256: String,
+    length: u64,
+}
+
+/// Streams every chunk in `store`, the db file at `db_path` (if `Some`), and every thumbnail under
+/// `thumbnail_path` into a new zip archive at `archive_path`. `db_path` should be `None` for a
+/// repo not backed by the default embedded SQLite `DB` (see `Repo::export`), even though that
+/// repo may still have a stale `vorg.db` stub file on disk.
+///
+/// # Errors
+/// - `ErrorKind::IO` if `store`, `db_path`, or `thumbnail_path` cannot be read, or `archive_path`
+///   cannot be written.
+pub fn export(
+    chunk_hashes: &[String],
+    item_hashes: &[String],
+    store: &dyn Store,
+    db_path: Option<&Path>,
+    thumbnail_path: &Path,
+    archive_path: &Path,
+) -> Result<()> {
+    let file = fs::File::create(archive_path)?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default();
+    let mut members = Vec::new();
+
+    for hash in chunk_hashes {
+        let data = store.get(hash)?;
+        let path = chunk_member_path(hash);
+        zip.start_file(&path, options).map_err(archive_error)?;
+        zip.write_all(&data)?;
+        members.push(ArchiveMember {
+            path,
+            sha256: sha256_hex(&data),
+            length: data.len() as u64,
+        });
+    }
+
+    if let Some(db_path) = db_path.filter(|db_path| db_path.is_file()) {
+        members.push(write_member_from_file(
+            &mut zip,
+            options,
+            DB_MEMBER_PATH,
+            db_path,
+        )?);
+    }
+
+    for hash in item_hashes {
+        let thumbnail_file = thumbnail::path_for(thumbnail_path, hash);
+        if !thumbnail_file.is_file() {
+            continue;
+        }
+        members.push(write_member_from_file(
+            &mut zip,
+            options,
+            &thumbnail_member_path(hash),
+            &thumbnail_file,
+        )?);
+    }
+
+    zip.start_file(MANIFEST_MEMBER_PATH, options)
+        .map_err(archive_error)?;
+    zip.write_all(&encode_manifest(&members))?;
+
+    zip.finish().map_err(archive_error)?;
+    Ok(())
+}
+
+/// Outcome of `import`: which new chunks and whether a db file were restored, for the caller to
+/// report back to the user.
+pub struct ArchiveImportSummary {
+    pub chunks_restored: usize,
+    pub db_restored: bool,
+    pub thumbnails_restored: usize,
+}
+
+/// Extracts an archive written by `export` into the repo at `db_path`/`thumbnail_path`, writing
+/// every chunk through `store`.
+///
+/// `existing_chunk_hashes` must be `store`'s current content (sorted ascending); chunks the store
+/// already has are skipped rather than re-written, the same way `Repo::import_file` only writes a
+/// chunk the first time it's seen. The db member is only restored if `db_path` is `Some`, which
+/// the caller should pass only when its own catalog is empty: merging two catalogs isn't
+/// supported, only adding a fresh one.
+///
+/// # Errors
+/// - `ErrorKind::Archive` if the archive has no valid manifest, references a member it doesn't
+///   contain, a member's content doesn't match its recorded digest, or a `store`/`thumbnail`
+///   member's path doesn't carry a well-formed hash (rejected before it is ever used to build a
+///   filesystem path, so a manifest can't be used to write outside the repo).
+/// - `ErrorKind::IO` if the archive, `db_path`, or `thumbnail_path` cannot be read or written.
+pub fn import(
+    archive_path: &Path,
+    existing_chunk_hashes: &[String],
+    store: &dyn Store,
+    db_path: Option<&Path>,
+    thumbnail_path: &Path,
+) -> Result<ArchiveImportSummary> {
+    let file = fs::File::open(archive_path)?;
+    let mut zip = ZipArchive::new(file).map_err(archive_error)?;
+
+    let manifest_bytes = read_member_bytes(&mut zip, MANIFEST_MEMBER_PATH)?;
+    let members = decode_manifest(&manifest_bytes)?;
+
+    let mut archive_chunk_hashes: Vec<String> = Vec::new();
+    for member in &members {
+        if let Some(hash) = member.path.strip_prefix("store/") {
+            validate_member_hash(&member.path, hash, CHUNK_HASH_HEX_LEN)?;
+            archive_chunk_hashes.push(hash.to_string());
+        } else if let Some(hash) = member
+            .path
+            .strip_prefix("thumbnail/")
+            .and_then(|rest| rest.strip_suffix(".jpg"))
+        {
+            validate_member_hash(&member.path, hash, ITEM_HASH_HEX_LEN)?;
+        }
+    }
+    archive_chunk_hashes.sort();
+    let (missing, _unexpected_in_archive) =
+        utils::reconcile_sorted_hashes(&archive_chunk_hashes, existing_chunk_hashes);
+
+    let mut result = ArchiveImportSummary {
+        chunks_restored: 0,
+        db_restored: false,
+        thumbnails_restored: 0,
+    };
+
+    for member in &members {
+        if member.path == MANIFEST_MEMBER_PATH {
+            continue;
+        }
+
+        if let Some(hash) = member.path.strip_prefix("store/") {
+            if !missing.contains(&hash.to_string()) {
+                continue;
+            }
+            let data = read_verified_member(&mut zip, member)?;
+            store.put(hash, &data)?;
+            result.chunks_restored += 1;
+            continue;
+        }
+
+        if member.path == DB_MEMBER_PATH {
+            let Some(db_path) = db_path else {
+                continue;
+            };
+            let data = read_verified_member(&mut zip, member)?;
+            fs::write(db_path, data)?;
+            result.db_restored = true;
+            continue;
+        }
+
+        if let Some(hash) = member
+            .path
+            .strip_prefix("thumbnail/")
+            .and_then(|rest| rest.strip_suffix(".jpg"))
+        {
+            let thumbnail_file = thumbnail::path_for(thumbnail_path, hash);
+            if thumbnail_file.is_file() {
+                continue;
+            }
+            let data = read_verified_member(&mut zip, member)?;
+            if let Some(parent) = thumbnail_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(thumbnail_file, data)?;
+            result.thumbnails_restored += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Length in hex chars of a chunk hash (BLAKE3, see `chunking::chunk_file`).
+const CHUNK_HASH_HEX_LEN: usize = 64;
+
+/// Rejects any `hash` that isn't exactly `expected_len` lowercase hex digits.
+///
+/// `hash` comes straight from a manifest path the archive author fully controls, and both
+/// `Store::put` (`store::LocalFsStore::chunk_path`) and `thumbnail::path_for` blindly slice and
+/// join it into a filesystem path (`hash[0..2]`/`hash[2..]`) without checking its shape first.
+/// Without this check a manifest entry like `store/../../../../home/user/.ssh/authorized_keys`
+/// (or any hash containing `/`) would let `import` write an attacker-chosen file to an
+/// attacker-chosen path outside the repo, and a too-short hash would panic the slice instead.
+fn validate_member_hash(path: &str, hash: &str, expected_len: usize) -> Result<()> {
+    if utils::is_lowercase_hex(hash, expected_len) {
+        Ok(())
+    } else {
+        Err(Error::with_args(
+            ErrorKind::Archive,
+            "archive-member-path-invalid",
+            vec![("path", path.to_string())],
+        ))
+    }
+}
+
+fn chunk_member_path(hash: &str) -> String {
+    format!("store/{hash}")
+}
+
+fn thumbnail_member_path(hash: &str) -> String {
+    format!("thumbnail/{hash}.jpg")
+}
+
+/// Streams `source_path`'s content into `zip` under `member_path`, returning the written member's
+/// digest and length.
+fn write_member_from_file<W: Write + io::Seek>(
+    zip: &mut ZipWriter<W>,
+    options: FileOptions,
+    member_path: &str,
+    source_path: &Path,
+) -> Result<ArchiveMember> {
+    zip.start_file(member_path, options).map_err(archive_error)?;
+    let mut source = fs::File::open(source_path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut length = 0u64;
+    loop {
+        let read = source.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        zip.write_all(&buffer[..read])?;
+        hasher.update(&buffer[..read]);
+        length += read as u64;
+    }
+    Ok(ArchiveMember {
+        path: member_path.to_string(),
+        sha256: hex::encode(hasher.finalize()),
+        length,
+    })
+}
+
+/// Reads `member`'s bytes out of `zip` and checks them against its recorded digest.
+fn read_verified_member<R: Read + io::Seek>(
+    zip: &mut ZipArchive<R>,
+    member: &ArchiveMember,
+) -> Result<Vec<u8>> {
+    let data = read_member_bytes(zip, &member.path)?;
+    let actual = sha256_hex(&data);
+    if actual != member.sha256 {
+        return Err(Error::with_args(
+            ErrorKind::Archive,
+            "archive-digest-mismatch",
+            vec![
+                ("path", member.path.clone()),
+                ("expected", member.sha256.clone()),
+                ("actual", actual),
+            ],
+        ));
+    }
+    Ok(data)
+}
+
+fn read_member_bytes<R: Read + io::Seek>(zip: &mut ZipArchive<R>, path: &str) -> Result<Vec<u8>> {
+    let mut entry = zip.by_name(path).map_err(|_| {
+        Error::with_args(
+            ErrorKind::Archive,
+            "archive-member-missing",
+            vec![("path", path.to_string())],
+        )
+    })?;
+    let mut data = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Serializes `members` as plain `path|sha256|length` lines, one per member, matching the
+/// hand-rolled format `manifest::Manifest` uses rather than pulling in a serialization crate.
+fn encode_manifest(members: &[ArchiveMember]) -> Vec<u8> {
+    let mut text = String::new();
+    for member in members {
+        text.push_str(&format!("{}|{}|{}\n", member.path, member.sha256, member.length));
+    }
+    text.into_bytes()
+}
+
+fn decode_manifest(bytes: &[u8]) -> Result<Vec<ArchiveMember>> {
+    let text = String::from_utf8(bytes.to_vec())
+        .map_err(|_| Error::new(ErrorKind::Archive, "archive-manifest-invalid"))?;
+
+    let mut members = Vec::new();
+    for line in text.lines() {
+        let mut fields = line.splitn(3, '|');
+        let (Some(path), Some(sha256), Some(length)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(Error::new(ErrorKind::Archive, "archive-manifest-invalid"));
+        };
+        let length = length
+            .parse()
+            .map_err(|_| Error::new(ErrorKind::Archive, "archive-manifest-invalid"))?;
+        members.push(ArchiveMember {
+            path: path.to_string(),
+            sha256: sha256.to_string(),
+            length,
+        });
+    }
+    Ok(members)
+}
+
+fn archive_error(detail: impl std::fmt::Display) -> Error {
+    Error::with_args(
+        ErrorKind::Archive,
+        "archive-manifest-invalid",
+        vec![("detail", detail.to_string())],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+
+    fn chunk_hash(fill: char) -> String {
+        fill.to_string().repeat(CHUNK_HASH_HEX_LEN)
+    }
+
+    fn item_hash(fill: char) -> String {
+        fill.to_string().repeat(ITEM_HASH_HEX_LEN)
+    }
+
+    #[test]
+    fn export_then_import_restores_chunks_db_and_thumbnails() {
+        let dir = std::env::temp_dir().join("vorg-archive-test-roundtrip");
+        fs::create_dir_all(&dir).unwrap();
+        let archive_path = dir.join("pod.zip");
+        let db_path = dir.join("vorg.db");
+        let thumbnail_path = dir.join("thumbnail");
+        fs::create_dir_all(&thumbnail_path).unwrap();
+        fs::write(&db_path, b"fake sqlite db content").unwrap();
+        let item1 = item_hash('1');
+        let thumbnail_file = thumbnail::path_for(&thumbnail_path, &item1);
+        fs::create_dir_all(thumbnail_file.parent().unwrap()).unwrap();
+        fs::write(&thumbnail_file, b"fake jpeg").unwrap();
+
+        let chunk1 = chunk_hash('a');
+        let chunk2 = chunk_hash('b');
+        let store = MemoryStore::new();
+        store.put(&chunk1, b"hello").unwrap();
+        store.put(&chunk2, b"world").unwrap();
+
+        export(
+            &[chunk1.clone(), chunk2.clone()],
+            &[item1.clone()],
+            &store,
+            Some(&db_path),
+            &thumbnail_path,
+            &archive_path,
+        )
+        .unwrap();
+
+        let target_store = MemoryStore::new();
+        let target_db_path = dir.join("restored-vorg.db");
+        let target_thumbnail_path = dir.join("restored-thumbnail");
+        fs::create_dir_all(&target_thumbnail_path).unwrap();
+
+        let result = import(
+            &archive_path,
+            &[],
+            &target_store,
+            Some(&target_db_path),
+            &target_thumbnail_path,
+        )
+        .unwrap();
+
+        assert_eq!(result.chunks_restored, 2);
+        assert!(result.db_restored);
+        assert_eq!(result.thumbnails_restored, 1);
+        assert_eq!(target_store.get(&chunk1).unwrap(), b"hello");
+        assert_eq!(target_store.get(&chunk2).unwrap(), b"world");
+        assert_eq!(fs::read(&target_db_path).unwrap(), b"fake sqlite db content");
+        assert_eq!(
+            fs::read(thumbnail::path_for(&target_thumbnail_path, &item1)).unwrap(),
+            b"fake jpeg"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn import_skips_chunks_already_in_the_target_store() {
+        let dir = std::env::temp_dir().join("vorg-archive-test-merge");
+        fs::create_dir_all(&dir).unwrap();
+        let archive_path = dir.join("pod.zip");
+        let thumbnail_path = dir.join("thumbnail");
+        fs::create_dir_all(&thumbnail_path).unwrap();
+
+        let chunk1 = chunk_hash('c');
+        let store = MemoryStore::new();
+        store.put(&chunk1, b"hello").unwrap();
+        export(
+            &[chunk1.clone()],
+            &[],
+            &store,
+            None,
+            &thumbnail_path,
+            &archive_path,
+        )
+        .unwrap();
+
+        let target_store = MemoryStore::new();
+        let result = import(
+            &archive_path,
+            &[chunk1.clone()],
+            &target_store,
+            None,
+            &dir.join("restored-thumbnail"),
+        )
+        .unwrap();
+
+        assert_eq!(result.chunks_restored, 0);
+        assert!(!target_store.exists(&chunk1));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn import_rejects_a_store_member_with_a_path_traversal_hash() {
+        let dir = std::env::temp_dir().join("vorg-archive-test-traversal");
+        fs::create_dir_all(&dir).unwrap();
+        let archive_path = dir.join("pod.zip");
+        let escape_target = dir.join("escaped.txt");
+
+        let members = vec![ArchiveMember {
+            path: format!("store/../../../../../../../../..{}", escape_target.display()),
+            sha256: sha256_hex(b"pwned"),
+            length: 5,
+        }];
+        write_malicious_archive(&archive_path, &members, b"pwned");
+
+        let target_store = MemoryStore::new();
+        let result = import(
+            &archive_path,
+            &[],
+            &target_store,
+            None,
+            &dir.join("thumbnail"),
+        );
+
+        assert!(matches!(result, Err(e) if e.kind == ErrorKind::Archive));
+        assert!(!escape_target.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn import_rejects_a_store_member_with_a_too_short_hash() {
+        let dir = std::env::temp_dir().join("vorg-archive-test-short-hash");
+        fs::create_dir_all(&dir).unwrap();
+        let archive_path = dir.join("pod.zip");
+
+        let members = vec![ArchiveMember {
+            path: "store/a".to_string(),
+            sha256: sha256_hex(b"x"),
+            length: 1,
+        }];
+        write_malicious_archive(&archive_path, &members, b"x");
+
+        let target_store = MemoryStore::new();
+        let result = import(
+            &archive_path,
+            &[],
+            &target_store,
+            None,
+            &dir.join("thumbnail"),
+        );
+
+        assert!(matches!(result, Err(e) if e.kind == ErrorKind::Archive));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Writes a zip archive with a hand-crafted manifest, bypassing `export`'s own (safe) path
+    /// construction so a malicious/malformed manifest can be fed to `import` in isolation.
+    fn write_malicious_archive(archive_path: &Path, members: &[ArchiveMember], payload: &[u8]) {
+        let file = fs::File::create(archive_path).unwrap();
+        let mut zip = ZipWriter::new(file);
+        let options = FileOptions::default();
+        zip.start_file(MANIFEST_MEMBER_PATH, options).unwrap();
+        zip.write_all(&encode_manifest(members)).unwrap();
+        for member in members {
+            zip.start_file(&member.path, options).unwrap();
+            zip.write_all(payload).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn manifest_roundtrips_through_its_text_encoding() {
+        let members = vec![
+            ArchiveMember {
+                path: "store/abc".to_string(),
+                sha256: "deadbeef".to_string(),
+                length: 5,
+            },
+            ArchiveMember {
+                path: "db/vorg.db".to_string(),
+                sha256: "feedface".to_string(),
+                length: 42,
+            },
+        ];
+        let bytes = encode_manifest(&members);
+        let decoded = decode_manifest(&bytes).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].path, "store/abc");
+        assert_eq!(decoded[0].sha256, "deadbeef");
+        assert_eq!(decoded[0].length, 5);
+        assert_eq!(decoded[1].path, "db/vorg.db");
+    }
+}
@@ -0,0 +1,156 @@
+//! Content-defined chunking (CDC) used by the dedup store beneath `store/`.
+//!
+//! Files are split into variable-length chunks using a Gear/FastCDC-style rolling hash so that
+//! near-identical files share chunks instead of being stored whole. See `Repo::import` in
+//! `lib.rs` for how chunks are assembled back into the chunk list recorded in the db.
+
+use crate::error::{Error, ErrorKind, Result};
+use lazy_static::lazy_static;
+use std::io::Read;
+
+/// Smallest allowed chunk size, in bytes.
+pub const MIN_CHUNK_SIZE: usize = 2 * 1024;
+/// Largest allowed chunk size, in bytes.
+pub const MAX_CHUNK_SIZE: usize = 64 * 1024;
+/// Chunk size the rolling hash mask targets on average.
+pub const TARGET_CHUNK_SIZE: usize = 8 * 1024;
+
+/// A single content-defined chunk of an imported file.
+pub struct Chunk {
+    pub hash: String,
+    pub data: Vec<u8>,
+}
+
+lazy_static! {
+    /// Per-byte fingerprint table for the Gear hash, seeded from a fixed PRNG so the table is
+    /// stable across runs (chunk boundaries must be reproducible).
+    static ref GEAR: [u64; 256] = {
+        let mut table = [0u64; 256];
+        let mut state: u64 = 0x9E3779B97F4A7C15;
+        for slot in table.iter_mut() {
+            // splitmix64
+            state = state.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            *slot = z ^ (z >> 31);
+        }
+        table
+    };
+}
+
+/// Mask applied to the rolling fingerprint to declare a cut point. Has roughly
+/// `log2(TARGET_CHUNK_SIZE)` low bits set.
+fn cut_mask() -> u64 {
+    (TARGET_CHUNK_SIZE as u64).next_power_of_two() - 1
+}
+
+/// Splits `data` into content-defined chunks, always cutting at EOF.
+///
+/// Chunk boundaries are found with a Gear-style rolling hash: `fp = (fp << 1) + GEAR[byte]`, with
+/// a cut declared when `fp & mask == 0`. Chunk length is clamped to
+/// `[MIN_CHUNK_SIZE, MAX_CHUNK_SIZE]`.
+pub fn chunk_bytes(data: &[u8]) -> Vec<Chunk> {
+    let mask = cut_mask();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut fp: u64 = 0;
+
+    for (offset, &byte) in data.iter().enumerate() {
+        let len = offset - start + 1;
+        fp = (fp << 1).wrapping_add(GEAR[byte as usize]);
+
+        let at_cut = len >= MIN_CHUNK_SIZE && fp & mask == 0;
+        let must_cut = len >= MAX_CHUNK_SIZE;
+        if at_cut || must_cut {
+            chunks.push(make_chunk(&data[start..=offset]));
+            start = offset + 1;
+            fp = 0;
+        }
+    }
+
+    if start < data.len() {
+        chunks.push(make_chunk(&data[start..]));
+    }
+
+    chunks
+}
+
+fn make_chunk(bytes: &[u8]) -> Chunk {
+    Chunk {
+        hash: blake3::hash(bytes).to_hex().to_string(),
+        data: bytes.to_vec(),
+    }
+}
+
+/// Reads `path` fully and splits it into content-defined chunks.
+///
+/// # Errors
+/// - `ErrorKind::IO` if `path` cannot be read.
+pub fn chunk_file<T>(path: T) -> Result<Vec<Chunk>>
+where
+    T: AsRef<std::path::Path>,
+{
+    let mut file = std::fs::File::open(path).map_err(Error::from)?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).map_err(Error::from)?;
+    Ok(chunk_bytes(&data))
+}
+
+/// Hashes a single chunk's content the same way `chunk_bytes` does, for integrity verification.
+pub fn hash_chunk(data: &[u8]) -> String {
+    blake3::hash(data).to_hex().to_string()
+}
+
+/// Returns `ErrorKind::IO` wrapping a message about a missing chunk, for use by
+/// `check_data_integrity`.
+pub fn missing_chunk_error(chunk_hash: &str) -> Error {
+    Error::with_args(
+        ErrorKind::IO,
+        "missing-chunk",
+        vec![("hash", chunk_hash.to_string())],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input_produces_no_chunks() {
+        assert!(chunk_bytes(&[]).is_empty());
+    }
+
+    #[test]
+    fn small_input_is_a_single_chunk() {
+        let data = vec![1u8; 100];
+        let chunks = chunk_bytes(&data);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].data, data);
+    }
+
+    #[test]
+    fn chunks_never_exceed_max_size() {
+        let data = vec![7u8; MAX_CHUNK_SIZE * 3 + 123];
+        let chunks = chunk_bytes(&data);
+        for chunk in &chunks {
+            assert!(chunk.data.len() <= MAX_CHUNK_SIZE);
+        }
+        let total: usize = chunks.iter().map(|c| c.data.len()).sum();
+        assert_eq!(total, data.len());
+    }
+
+    #[test]
+    fn identical_prefixes_produce_identical_leading_chunks() {
+        let mut a = vec![0u8; 200_000];
+        for (i, byte) in a.iter_mut().enumerate() {
+            *byte = (i % 251) as u8;
+        }
+        let mut b = a.clone();
+        b.extend_from_slice(b"trailing metadata that differs");
+
+        let chunks_a = chunk_bytes(&a);
+        let chunks_b = chunk_bytes(&b);
+        assert_eq!(chunks_a[0].hash, chunks_b[0].hash);
+    }
+}
@@ -0,0 +1,140 @@
+//! Pluggable catalog backend, analogous to `store::Store` for the content-addressed chunk store.
+//!
+//! `Repo` used to hold a concrete SQLite `db::DB`, which tied every vorg catalog to a single
+//! embedded file. `Database` abstracts the operations `Repo` actually needs from it: import,
+//! filtered listing, the hash listings `check_data_integrity` scans, and deletion. That lets a
+//! catalog instead live in a shared Postgres instance (see `postgres_database::PostgresDatabase`),
+//! so multiple machines can see the same catalog while still writing chunks to a shared,
+//! network-mounted `Store`. This follows the same trait-plus-implementations shape as
+//! `Store`/`LocalFsStore`/`MemoryStore`.
+
+use crate::{
+    db::{Item, QueryPage},
+    error::Result,
+    filter::Filter,
+    media::MediaKind,
+    metadata::MediaMetadata,
+    pagination::{ItemOrder, ItemPage},
+    query::FilterExpr,
+};
+use async_trait::async_trait;
+
+/// Catalog operations `Repo` performs against whichever database backend is configured.
+///
+/// Implementations need not be safe to share across concurrent callers; `Repo` only ever holds
+/// one `Box<dyn Database>` and calls into it sequentially, the same way `db::DB` already assumed
+/// a single `SqliteConnection`.
+#[async_trait]
+pub trait Database: Send + Sync {
+    /// Returns whether a chunk with `hash` is already known to the catalog.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn chunk_exists(&mut self, hash: &str) -> Result<bool>;
+
+    /// Returns the subset of `hashes` already known to the catalog, in a single round-trip rather
+    /// than one `chunk_exists` call per hash. `Repo::import_file` uses this to decide which of a
+    /// file's content-defined chunks it actually needs to write to the `Store`, and which already
+    /// exist there because some other item shares that chunk.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn chunks_exist(&mut self, hashes: &[String]) -> Result<Vec<String>>;
+
+    /// Imports a file into the catalog, tagging it with `tags`, recording the technical
+    /// `metadata` probed from it, and recording it as an ordered list of content-defined chunks.
+    ///
+    /// # Errors
+    /// - `ErrorKind::Duplicate` if an item with `hash` already exists.
+    /// - `ErrorKind::DB` if the underlying query fails.
+    #[allow(clippy::too_many_arguments)]
+    async fn import_file_chunked(
+        &mut self,
+        title: &str,
+        hash: &str,
+        ext: &str,
+        media_kind: MediaKind,
+        tags: &[String],
+        metadata: &MediaMetadata,
+        chunks: &[(String, i64)],
+    ) -> Result<()>;
+
+    /// Gets items that satisfy `filter`, see `filter::Filter`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn get_items(&mut self, filter: &Filter) -> Result<Vec<Item>>;
+
+    /// Returns one page of items that satisfy `filter`, ordered by `order`, seeking past `cursor`
+    /// (the `next_cursor` of a previous page, or `None` to start from the beginning). `page_size`
+    /// bounds how many items the page holds; see `pagination::ItemPage`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    /// - `ErrorKind::InvalidCursor` if `cursor` is not a cursor this method itself produced.
+    async fn list_items_page(
+        &mut self,
+        filter: &Filter,
+        order: ItemOrder,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<ItemPage>;
+
+    /// Returns items matching `filter`, a `query::FilterExpr` boolean tag/title expression; see
+    /// `db::DB::query_items` for how it differs from `get_items`'s simpler `Filter`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn query_items(&mut self, filter: &FilterExpr) -> Result<Vec<Item>>;
+
+    /// Like `query_items`, but paginated via `limit`/`offset` and reporting the total number of
+    /// matches alongside the page; see `db::DB::query_items_page`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn query_items_page(
+        &mut self,
+        filter: &FilterExpr,
+        limit: usize,
+        offset: usize,
+    ) -> Result<QueryPage>;
+
+    /// Returns all known chunk hashes, sorted ascending, for use by `check_data_integrity`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn get_all_chunk_hashes(&mut self) -> Result<Vec<String>>;
+
+    /// Returns all item hashes, for cross-referencing against generated thumbnails in
+    /// `check_data_integrity`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn get_all_item_hashes(&mut self) -> Result<Vec<String>>;
+
+    /// Returns the chunk hashes making up the item identified by `hash`, in original chunking
+    /// order, or `None` if no such item exists. `check_data_integrity` uses this to reassemble an
+    /// item's content and re-derive its extension.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn get_item_chunk_hashes(&mut self, hash: &str) -> Result<Option<Vec<String>>>;
+
+    /// Updates the extension recorded for the item identified by `hash`, e.g. after
+    /// `Repo::repair` re-derives it from the item's actual content.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn update_item_extension(&mut self, hash: &str, ext: &str) -> Result<()>;
+
+    /// Deletes the item identified by `hash`, along with its collection, tags, and chunk
+    /// references, decrementing the `refcount` of each chunk it referenced.
+    ///
+    /// Returns the hashes of any chunks whose `refcount` dropped to zero as a result: the caller
+    /// (`Repo::delete_item`) still owns the `Store`, so it is responsible for actually unlinking
+    /// those blobs once this call's transaction has committed them as unreferenced.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    async fn delete_item(&mut self, hash: &str) -> Result<Vec<String>>;
+}
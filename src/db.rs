@@ -1,47 +1,156 @@
 use crate::{
+    database::Database,
     error::{Error, ErrorKind, Result},
-    utils::{self, ListCompareResult},
+    filter::Filter,
+    history::{Change, ChangeOperation},
+    media::MediaKind,
+    metadata::MediaMetadata,
+    pagination::{self, ItemOrder, ItemPage},
+    query::FilterExpr,
+    utils::{self, ITEM_HASH_HEX_LEN},
 };
+use sha2::{Digest, Sha224};
 use sqlx::{
     migrate::MigrateDatabase,
-    sqlite::{SqliteConnectOptions, SqliteRow},
-    ConnectOptions, Connection, Row, Sqlite, SqliteConnection,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow},
+    ConnectOptions, Connection, FromRow, Row, Sqlite, SqliteConnection, SqlitePool,
 };
-use std::{fs, path::Path, str::FromStr};
+use std::{
+    fs,
+    future::Future,
+    io::{Read, Write},
+    path::Path,
+    pin::Pin,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A closure's return type for `DB::with_transaction`/`Tx::with_transaction`: stable Rust has no
+/// way to express "a generic future borrowing from this call's argument" other than naming the
+/// borrow's lifetime on a boxed trait object, so callers return `Box::pin(async move { ... })`.
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
 
 pub struct DB {
     connection: SqliteConnection,
 }
 
+/// How many connections `DB::open_read_only` pools, so that many concurrent readers don't queue
+/// behind a single connection the way they would on `DB`'s dedicated one.
+const READ_POOL_SIZE: u32 = 4;
+
+/// Max hashes per `WHERE hash IN (...)` round-trip in `DB::chunks_exist`, comfortably under
+/// SQLite's bound-parameter limit (999 on older builds) even for a file chunked into tens of
+/// thousands of pieces.
+const CHUNKS_EXIST_BATCH_SIZE: usize = 500;
+
+/// A read-only, pool-backed handle opened by `DB::open_read_only`, safe to hand to e.g. UI code
+/// that should browse a repo without being able to mutate it.
+///
+/// `ReadDb` only exposes the read queries (`get_items`, `list_items_page`, `search_collections`,
+/// `query_items`, `query_items_page`); unlike `DB`, it has no `import_file`/`add_tag_to_collection`/etc., so a write
+/// attempt is a compile error rather than a runtime one. Each call checks out one of
+/// `READ_POOL_SIZE` pooled connections rather than serializing on a single one, so several readers
+/// can run concurrently.
+pub struct ReadDb {
+    pool: SqlitePool,
+}
+
 pub struct Item {
     pub hash: String,
     pub title: String,
     pub ext: String,
+    pub media_kind: MediaKind,
     pub collection_id: i64,
     pub tags: Vec<String>,
+    /// Unix timestamp (seconds) this item was imported, stamped once by `add_item_to_collection`.
+    pub imported_at: i64,
+    /// Technical metadata probed at import time, see `metadata::MediaMetadata`. `None` fields mean
+    /// either the item predates this probing or the probe failed on that particular file.
+    pub metadata: MediaMetadata,
 }
 
 impl sqlx::FromRow<'_, SqliteRow> for Item {
     fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        let media_kind_str: String = row.try_get("media_kind")?;
         Ok(Item {
             hash: row.try_get("hash")?,
             title: row.try_get("title")?,
             ext: row.try_get("ext")?,
+            media_kind: MediaKind::from_db_str(&media_kind_str).ok_or_else(|| {
+                sqlx::Error::Decode(
+                    format!("unrecognized media_kind {media_kind_str:?} in items table").into(),
+                )
+            })?,
             collection_id: row.try_get("collection_id")?,
             tags: Vec::new(),
+            imported_at: row.try_get("imported_at")?,
+            metadata: MediaMetadata {
+                duration: row.try_get("duration")?,
+                width: row.try_get("width")?,
+                height: row.try_get("height")?,
+                frame_rate: row.try_get("frame_rate")?,
+                container: row.try_get("container")?,
+                video_codec: row.try_get("video_codec")?,
+                audio_codec: row.try_get("audio_codec")?,
+                bitrate: row.try_get("bitrate")?,
+            },
         })
     }
 }
 
+/// One page of a `DB::query_items_page`/`ReadDb::query_items_page` listing.
+pub struct QueryPage {
+    pub items: Vec<Item>,
+    /// How many collections match the filter in total, ignoring `limit`/`offset` — not just how
+    /// many are in this page — so a caller can render "page N of M" controls.
+    pub total_count: usize,
+}
+
+/// Seconds since the Unix epoch, stamped onto an item once at import time. Falls back to 0 in the
+/// practically-impossible case the system clock reads before the epoch, rather than panicking.
+fn now_unix_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+/// Turns free-form text into a literal FTS5 query: splits `query` on whitespace and wraps each
+/// term in double quotes, doubling any embedded quote (FTS5's own escape for a literal `"` inside
+/// a quoted string). This way a title like `foo "bar" AND baz` is searched for as four literal
+/// terms rather than being parsed as FTS5 query syntax (where `AND` is an operator and a bare `"`
+/// opens an unterminated string).
+fn sanitize_fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Derives a new host id from process-local entropy (PID, wall-clock time), hashed through
+/// `Sha224` the same way `Repo::hash`/`Repo::hash_bytes` hash file content, rather than pulling in
+/// a UUID generator for something this crate only ever needs to generate once per repo (see
+/// `DB::host_id`).
+fn generate_host_id() -> String {
+    let mut hasher = Sha224::new();
+    hasher.update(std::process::id().to_be_bytes());
+    let now_nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    hasher.update(now_nanos.to_be_bytes());
+    hex::encode(hasher.finalize())
+}
+
 impl DB {
     /// Create or connect to a vorg db.
     ///
     /// If the db does not exist, this creates a new vorg db.
-    /// If the db does exist, this connects to the db.
+    /// If the db does exist, this connects to the db and brings its schema up to date via
+    /// `DB::migrate`.
     ///
     /// # Errors
     /// - `ErrorKind::DB` when encountered database error either when creating a new database or
-    ///   opening/validating an existing one, e.g. invalid database or table structure.
+    ///   opening/migrating an existing one, e.g. the database was created by a newer vorg.
     /// - `ErrorKind::IO` when encountered IO error creating the parent folder of `db_path`, if it
     ///   does not exist.
     pub async fn new<T>(db_path: T) -> Result<Self>
@@ -52,437 +161,613 @@ impl DB {
         let db_path = db_path.as_ref();
         let db_path_string = db_path.to_string_lossy().into_owned();
 
-        // Check for db existence
-        if Sqlite::database_exists(&db_path_string).await? {
-            // Database exists
-            let mut connection = SqliteConnectOptions::from_str(&db_path_string)?
-                .connect()
-                .await?;
-            DB::validate_db(&mut connection)
-                .await
-                .map(|_| DB { connection })
-        } else {
-            // Database does not exist, create a new one
+        // Create the underlying sqlite file first if it doesn't exist yet; `migrate` handles
+        // populating its schema either way.
+        if !Sqlite::database_exists(&db_path_string).await? {
             let db_path_parent = db_path
                 .parent()
                 .expect("Database's path should have a parent, i.e. not root.");
             fs::create_dir_all(db_path_parent)?;
-            DB::create_db(&db_path_string)
-                .await
-                .map(|connection| DB { connection })
+            Sqlite::create_database(&db_path_string).await?;
         }
+
+        let mut connection = SqliteConnectOptions::from_str(&db_path_string)?
+            .connect()
+            .await?;
+        DB::migrate(&mut connection).await?;
+        Ok(DB { connection })
     }
 
-    /// Creates a new sqlite db to be used as vorg db.
+    /// Opens an existing vorg db read-only, returning a pooled `ReadDb` rather than a `DB`.
     ///
-    /// This function assumes the database does not exist. This is enforced by create_repo which
-    /// ensures the repo folder is empty before calling this function.
-    /// This function also requires that the parent of `db_path_str` exists and is a folder.
-    async fn create_db(db_path_str: &str) -> Result<SqliteConnection> {
-        // Create database and connect to it
-        Sqlite::create_database(db_path_str).await?;
-        let mut connection = SqliteConnection::connect(db_path_str).await?;
-
-        // Initialize tables
-        sqlx::query(
-        "
-            CREATE TABLE tags (
-                tag_id INTEGER PRIMARY KEY NOT NULL,
-                name TEXT NOT NULL
-            );
-            CREATE TABLE collections (
-                collection_id INTEGER PRIMARY KEY NOT NULL,
-                title TEXT NOT NULL
-            );
-            CREATE TABLE items (
-                item_id INTEGER PRIMARY KEY NOT NULL,
-                collection_id INTEGER NOT NULL,
-                ext TEXT NOT NULL,
-                hash VARCHAR(64) NOT NULL,
-                FOREIGN KEY (collection_id) REFERENCES collections(collection_id)
-            );
-            CREATE TABLE collection_tag (
-                collection_id INTEGER NOT NULL,
-                tag_id INTEGER NOT NULL,
-                PRIMARY KEY (collection_id, tag_id),
-                FOREIGN KEY (collection_id) REFERENCES collections(collection_id),
-                FOREIGN KEY (tag_id) REFERENCES tags(tag_id)
-            );
-            CREATE VIRTUAL TABLE title_fts USING fts5(
-                title,
-                content='collections',
-                content_rowid='collection_id'
-            );
-            CREATE TRIGGER title_insert AFTER INSERT ON collections BEGIN
-                INSERT INTO title_fts(rowid, title) VALUES (new.collection_id, new.title);
-            END;
-            CREATE TRIGGER title_delete AFTER DELETE ON collections BEGIN
-                INSERT INTO title_fts(title_fts, rowid, title)
-                    VALUES('delete', old.collection_id, old.title);
-            END;
-            CREATE TRIGGER title_update AFTER UPDATE ON collections BEGIN
-                INSERT INTO title_fts(fts_idx, rowid, title) VALUES('delete', old.collection_id, old.title);
-                INSERT INTO title_fts(rowid, title) VALUES (new.collection_id, new.title);
-            END;
-            CREATE UNIQUE INDEX hash_index ON items (hash);
-            CREATE UNIQUE INDEX tag_index ON tags (name);
-            "
-        ).execute(&mut connection).await?;
-
-        Ok(connection)
+    /// Unlike `DB::new`, this never creates the file or runs `DB::migrate` — both are writes, and
+    /// the whole point of `ReadDb` is a handle that can't write. Point it at a db some other `DB`
+    /// handle has already created and migrated.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if `db_path` does not exist or could not be opened, e.g. because it has
+    ///   not been migrated to a schema this binary understands yet.
+    pub async fn open_read_only<T>(db_path: T) -> Result<ReadDb>
+    where
+        T: AsRef<Path>,
+    {
+        let db_path_string = db_path.as_ref().to_string_lossy().into_owned();
+        let options = SqliteConnectOptions::from_str(&db_path_string)?.read_only(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(READ_POOL_SIZE)
+            .connect_with(options)
+            .await?;
+        Ok(ReadDb { pool })
     }
 
-    /// Validates the strcture of a vorg db.
+    /// Ordered schema migrations, modeled on `rusqlite_migration`/`refinery`: each entry is SQL
+    /// applied exactly once, in order, keyed by its position in this slice rather than an
+    /// explicit version number. Migration 0 is the schema `create_db` used to hand-roll before
+    /// this system existed.
     ///
-    /// If valid, returns no error.
-    /// If not valid, returns a `InvalidDatabase` error with a message describing why.
-    async fn validate_db(connection: &mut SqliteConnection) -> Result<()> {
-        static EXPECTED_TABLE_NAMES: [&str; 9] = [
-            "collection_tag",
-            "collections",
-            "items",
-            "tags",
-            "title_fts",
-            "title_fts_config",
-            "title_fts_data",
-            "title_fts_docsize",
-            "title_fts_idx",
-        ];
-        static EXPECTED_INDICES: [&str; 2] = ["hash_index", "tag_index"];
-        static EXPECTED_TRIGGERS: [&str; 3] = ["title_delete", "title_insert", "title_update"];
-        static VERIFY_COLUMNS: [bool; 9] =
-            [true, true, true, true, false, false, false, false, false];
-        static EXPECTED_COLUMNS: [(usize, [(&str, &str); 4]); 4] = [
-            // collection_tag
-            (
-                2,
-                [
-                    ("collection_id", "INTEGER"),
-                    ("tag_id", "INTEGER"),
-                    ("", ""),
-                    ("", ""),
-                ],
-            ),
-            // collections
-            (
-                2,
-                [
-                    ("collection_id", "INTEGER"),
-                    ("title", "TEXT"),
-                    ("", ""),
-                    ("", ""),
-                ],
-            ),
-            // items
-            (
-                4,
-                [
-                    ("collection_id", "INTEGER"),
-                    ("ext", "TEXT"),
-                    ("hash", "VARCHAR(64)"),
-                    ("item_id", "INTEGER"),
-                ],
-            ),
-            // tags
-            (
-                2,
-                [("name", "TEXT"), ("tag_id", "INTEGER"), ("", ""), ("", "")],
-            ),
-        ];
-
-        let result = sqlx::query!(
-            "
-            SELECT tbl_name from sqlite_master
-            WHERE type='table' ORDER BY tbl_name
-            "
-        )
-        .map(|row| row.tbl_name)
-        .fetch_all(&mut *connection)
-        .await?;
-        let table_names: Vec<&str> = result
-            .iter()
-            .filter_map(|tbl_name_option| {
-                tbl_name_option
-                    .as_ref()
-                    .and_then(|tbl_name| Some(tbl_name.as_str()))
-            })
-            .collect();
-
-        // Validate table name
-        let compare_result = utils::compare_lists(
-            &table_names,
-            &EXPECTED_TABLE_NAMES,
-            |table_name| table_name,
-            |_, _| true,
+    /// Append new entries here for schema changes, each with its own test; never edit or reorder
+    /// an existing entry once it has shipped, since repos that already migrated past it have that
+    /// exact SQL baked into their history via `PRAGMA user_version`.
+    const MIGRATIONS: &[&str] = &[
+        // 0: initial schema
+        "
+        CREATE TABLE tags (
+            tag_id INTEGER PRIMARY KEY NOT NULL,
+            name TEXT NOT NULL
         );
-        match compare_result {
-            ListCompareResult::Missing(table_name) => {
-                return Err(Error {
-                    msg: format!("Table \"{table_name}\" is missing from the database.",),
-                    kind: ErrorKind::DB,
-                });
-            }
-            ListCompareResult::Unexpected(table_name) => {
-                return Err(Error {
-                    msg: format!("Unexpected table \"{table_name}\" exists in the database."),
-                    kind: ErrorKind::DB,
-                });
-            }
-            ListCompareResult::Unequal(_) => {
-                panic!("Unexpected compare result for table names!");
-            }
-            ListCompareResult::Identical => (),
-        }
-
-        // Validate table structure
-        let mut columns_index = 0;
-        for (index, table) in EXPECTED_TABLE_NAMES.iter().enumerate() {
-            if VERIFY_COLUMNS[index] {
-                DB::validate_table(
-                    connection,
-                    table,
-                    &EXPECTED_COLUMNS[columns_index].1,
-                    EXPECTED_COLUMNS[columns_index].0,
+        CREATE TABLE collections (
+            collection_id INTEGER PRIMARY KEY NOT NULL,
+            title TEXT NOT NULL
+        );
+        CREATE TABLE items (
+            item_id INTEGER PRIMARY KEY NOT NULL,
+            collection_id INTEGER NOT NULL,
+            ext TEXT NOT NULL,
+            hash VARCHAR(64) NOT NULL,
+            media_kind TEXT NOT NULL,
+            duration REAL,
+            width INTEGER,
+            height INTEGER,
+            frame_rate REAL,
+            container TEXT,
+            video_codec TEXT,
+            audio_codec TEXT,
+            bitrate INTEGER,
+            imported_at INTEGER NOT NULL,
+            FOREIGN KEY (collection_id) REFERENCES collections(collection_id)
+        );
+        CREATE TABLE chunks (
+            hash VARCHAR(64) PRIMARY KEY NOT NULL,
+            size INTEGER NOT NULL,
+            refcount INTEGER NOT NULL DEFAULT 1
+        );
+        CREATE TABLE item_chunks (
+            item_id INTEGER NOT NULL,
+            chunk_index INTEGER NOT NULL,
+            chunk_hash VARCHAR(64) NOT NULL,
+            PRIMARY KEY (item_id, chunk_index),
+            FOREIGN KEY (item_id) REFERENCES items(item_id),
+            FOREIGN KEY (chunk_hash) REFERENCES chunks(hash)
+        );
+        CREATE TABLE collection_tag (
+            collection_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (collection_id, tag_id),
+            FOREIGN KEY (collection_id) REFERENCES collections(collection_id),
+            FOREIGN KEY (tag_id) REFERENCES tags(tag_id)
+        );
+        CREATE VIRTUAL TABLE title_fts USING fts5(
+            title,
+            content='collections',
+            content_rowid='collection_id'
+        );
+        CREATE TRIGGER title_insert AFTER INSERT ON collections BEGIN
+            INSERT INTO title_fts(rowid, title) VALUES (new.collection_id, new.title);
+        END;
+        CREATE TRIGGER title_delete AFTER DELETE ON collections BEGIN
+            INSERT INTO title_fts(title_fts, rowid, title)
+                VALUES('delete', old.collection_id, old.title);
+        END;
+        CREATE TRIGGER title_update AFTER UPDATE ON collections BEGIN
+            INSERT INTO title_fts(fts_idx, rowid, title) VALUES('delete', old.collection_id, old.title);
+            INSERT INTO title_fts(rowid, title) VALUES (new.collection_id, new.title);
+        END;
+        CREATE UNIQUE INDEX hash_index ON items (hash);
+        CREATE UNIQUE INDEX tag_index ON tags (name);
+        ",
+        // 1: append-only change log (see history.rs) and a small key/value `meta` table backing
+        // `DB::host_id`
+        "
+        CREATE TABLE meta (
+            key TEXT PRIMARY KEY NOT NULL,
+            value TEXT NOT NULL
+        );
+        CREATE TABLE changes (
+            seq INTEGER PRIMARY KEY NOT NULL,
+            parent_seq INTEGER,
+            host_id TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            collection_id INTEGER,
+            tag TEXT,
+            created_at INTEGER NOT NULL
+        );
+        ",
+    ];
+
+    /// Brings `connection`'s schema up to date by applying every migration in
+    /// `DB::MIGRATIONS` past its recorded `PRAGMA user_version`, in a single transaction, then
+    /// advancing `user_version` to `DB::MIGRATIONS.len()`.
+    ///
+    /// A freshly created, empty database also reads `user_version` as 0, the same as a repo
+    /// created before this migration system existed (nothing ever stamped one). Those two cases
+    /// are told apart by checking whether the `items` table is already there: if so, migration 0
+    /// is exactly the schema that repo already has, so it's treated as already applied instead of
+    /// being re-run against tables that already exist.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if `user_version` is higher than `DB::MIGRATIONS.len()`, i.e. this
+    ///   binary is older than the database it's being pointed at.
+    /// - `ErrorKind::DB` if applying a migration fails; the transaction is rolled back, leaving
+    ///   the database exactly as it was found.
+    async fn migrate(connection: &mut SqliteConnection) -> Result<()> {
+        let user_version: i64 = sqlx::query_scalar("PRAGMA user_version")
+            .fetch_one(&mut *connection)
+            .await?;
+        let mut user_version = usize::try_from(user_version).unwrap_or(usize::MAX);
+
+        if user_version > DB::MIGRATIONS.len() {
+            return Err(Error::new(ErrorKind::DB, "database-too-new"));
+        }
+
+        if user_version == 0 {
+            let schema_exists: bool = sqlx::query_scalar(
+                "
+                SELECT EXISTS (
+                    SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'items'
                 )
-                .await?;
-                columns_index += 1;
+                ",
+            )
+            .fetch_one(&mut *connection)
+            .await?;
+            if schema_exists {
+                user_version = 1;
             }
         }
 
-        // Validate indices
-        let result = sqlx::query!(
-            "
-            SELECT name FROM sqlite_master
-            WHERE type = 'index'
-            AND sql IS NOT NULL
-            ORDER BY name
-            "
-        )
-        .map(|row| row.name)
-        .fetch_all(&mut *connection)
-        .await?;
-        let indices: Vec<&str> = result
-            .iter()
-            .filter_map(|index_name| index_name.as_ref().and_then(|name| Some(name.as_str())))
-            .collect();
-        let compare_result = utils::compare_lists(
-            &indices,
-            &EXPECTED_INDICES,
-            |index_name| index_name,
-            |_, _| true,
-        );
-        match compare_result {
-            ListCompareResult::Identical => (),
-            _ => {
-                return Err(Error {
-                    msg: format!("Database has unexpected or missing indices."),
-                    kind: ErrorKind::DB,
-                });
-            }
+        if user_version == DB::MIGRATIONS.len() {
+            return Ok(());
         }
 
-        // Validate triggers
-        let result = sqlx::query!(
-            "
-            SELECT name FROM sqlite_master
-            WHERE type = 'trigger'
-            ORDER BY name
-            "
-        )
-        .map(|row| row.name)
-        .fetch_all(&mut *connection)
-        .await?;
-        let triggers: Vec<&str> = result
-            .iter()
-            .filter_map(|index_name| index_name.as_ref().and_then(|name| Some(name.as_str())))
-            .collect();
-        let compare_result = utils::compare_lists(
-            &triggers,
-            &EXPECTED_TRIGGERS,
-            |index_name| index_name,
-            |_, _| true,
-        );
-        match compare_result {
-            ListCompareResult::Identical => (),
-            _ => {
-                return Err(Error {
-                    msg: format!("Database has unexpected or missing triggers."),
-                    kind: ErrorKind::DB,
-                });
-            }
+        if let Err(err) = DB::apply_migrations(connection, user_version).await {
+            sqlx::query("ROLLBACK TRANSACTION")
+                .execute(&mut *connection)
+                .await?;
+            return Err(err);
         }
 
         Ok(())
     }
 
-    /// Validates the strcture of a vorg db table.
-    ///
-    /// If valid, returns no error.
-    /// If not valid, returns a `InvalidDatabase` error with a message describing why.
-    async fn validate_table(
+    /// Runs every migration from `from_version` onward in a single transaction, bumps
+    /// `user_version` to `DB::MIGRATIONS.len()`, and commits. On error the transaction is left
+    /// open rather than committed, so `DB::migrate` can roll it back; a half-applied migration
+    /// must never be left standing.
+    async fn apply_migrations(
         connection: &mut SqliteConnection,
-        table_name: &str,
-        expected_columns: &[(&str, &str)],
-        expected_column_count: usize,
+        from_version: usize,
     ) -> Result<()> {
-        let columns: Vec<(String, String)> =
-            sqlx::query("SELECT name,type FROM pragma_table_info(?) ORDER BY name")
-                .bind(table_name)
-                .try_map(|row: SqliteRow| Ok((row.try_get("name")?, row.try_get("type")?)))
-                .fetch_all(connection)
-                .await?;
+        sqlx::query("BEGIN TRANSACTION")
+            .execute(&mut *connection)
+            .await?;
+        for migration in &DB::MIGRATIONS[from_version..] {
+            sqlx::query(migration).execute(&mut *connection).await?;
+        }
+        sqlx::query(&format!("PRAGMA user_version = {}", DB::MIGRATIONS.len()))
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query("COMMIT TRANSACTION")
+            .execute(&mut *connection)
+            .await?;
+        Ok(())
+    }
 
-        let columns: Vec<(&str, &str)> = columns
-            .iter()
-            .map(|column| (column.0.as_str(), column.1.as_str()))
-            .collect();
-
-        // Compare columns
-        let compare_result = utils::compare_lists(
-            &columns,
-            &expected_columns[..expected_column_count],
-            |column| &column.0,
-            |column_1, column_2| column_1.1 == column_2.1,
-        );
-        match compare_result {
-            ListCompareResult::Missing(column) => {
-                return Err(Error {
-                    msg: format!(
-                        "Column \"{}\" is missing from table \"{table_name}\".",
-                        column.0
-                    ),
-                    kind: ErrorKind::DB,
-                });
-            }
-            ListCompareResult::Unexpected(column) => {
-                return Err(Error {
-                    msg: format!(
-                        "Unexpected column \"{}\" in table \"{table_name}\".",
-                        column.0
-                    ),
-                    kind: ErrorKind::DB,
-                });
+    /// Runs `f` inside a transaction: commits if `f` returns `Ok`, issues `ROLLBACK` if it returns
+    /// `Err` (or, via `sqlx::Transaction`'s own `Drop`, if it panics) before propagating. `f` only
+    /// gets a `Tx`, not `self`, so it cannot reach `DB::with_transaction` again on the same
+    /// connection and commit out of band — the transaction can only end by `f` returning.
+    ///
+    /// `f` returns a boxed future rather than being declared `async fn` directly: stable Rust has
+    /// no way to write "a closure returning a future that borrows its argument" without naming
+    /// that borrowed lifetime, so callers wrap their body in `Box::pin(async move { ... })`.
+    async fn with_transaction<F, T>(&mut self, f: F) -> Result<T>
+    where
+        F: for<'t> FnOnce(&'t mut Tx<'_>) -> BoxFuture<'t, Result<T>>,
+    {
+        let mut tx = Tx {
+            connection: self.connection.begin().await?,
+        };
+        match f(&mut tx).await {
+            Ok(value) => {
+                tx.connection.commit().await?;
+                Ok(value)
             }
-            ListCompareResult::Unequal(column) => {
-                return Err(Error {
-                    msg: format!(
-                        "Column \"{}\" in table \"{table_name}\" should have type \"{}\".",
-                        column.0, column.1
-                    ),
-                    kind: ErrorKind::DB,
-                });
+            Err(err) => {
+                tx.connection.rollback().await?;
+                Err(err)
             }
-            ListCompareResult::Identical => (),
         }
-
-        Ok(())
     }
 
-    /// Start a new SQL transaction
-    async fn begin_transaction(&mut self) -> Result<()> {
-        sqlx::query!("BEGIN TRANSACTION")
-            .execute(&mut self.connection)
+    /// Returns this repo's stable per-machine id, generating and persisting one in the `meta`
+    /// table on first use so it stays the same across runs. Stamped onto every `Change` this
+    /// machine records, so a sync client can tell which host a change came from.
+    async fn host_id(&mut self) -> Result<String> {
+        let existing = sqlx::query!("SELECT value FROM meta WHERE key = 'host_id'")
+            .map(|row| row.value)
+            .fetch_optional(&mut self.connection)
             .await?;
-        Ok(())
+        if let Some(host_id) = existing {
+            return Ok(host_id);
+        }
+
+        let host_id = generate_host_id();
+        sqlx::query!(
+            "INSERT INTO meta(key, value) VALUES ('host_id', ?)",
+            host_id
+        )
+        .execute(&mut self.connection)
+        .await?;
+        Ok(host_id)
     }
 
-    /// Commit SQL transaction
-    async fn commit_transaction(&mut self) -> Result<()> {
-        sqlx::query!("COMMIT TRANSACTION")
-            .execute(&mut self.connection)
+    /// Returns the `seq` of the most recent change, or 0 if the log is empty, for a remote to
+    /// compare against its own last-known tip before calling `changes_since`.
+    pub async fn latest_seq(&mut self) -> Result<i64> {
+        let seq: Option<i64> = sqlx::query_scalar("SELECT MAX(seq) FROM changes")
+            .fetch_one(&mut self.connection)
             .await?;
-        Ok(())
+        Ok(seq.unwrap_or(0))
     }
 
-    /// Add a new collection in db
-    async fn add_collection(&mut self, title: &str) -> Result<i64> {
-        let collection_id = sqlx::query!(
+    /// Returns every change recorded after `seq`, oldest first, so a remote can catch up by
+    /// replaying only what it is missing rather than re-syncing the whole repo.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails, or if a stored `operation` is not one
+    ///   `ChangeOperation::from_db_str` recognizes (i.e. the db was written by a newer binary).
+    pub async fn changes_since(&mut self, seq: i64) -> Result<Vec<Change>> {
+        let rows = sqlx::query!(
             "
-            INSERT INTO collections(title) VALUES(?)
-            RETURNING collection_id;
+            SELECT seq, parent_seq, host_id, operation, collection_id, tag, created_at
+            FROM changes
+            WHERE seq > ?
+            ORDER BY seq
             ",
-            title
+            seq
         )
-        .map(|row| row.collection_id)
-        .fetch_one(&mut self.connection)
+        .fetch_all(&mut self.connection)
         .await?;
-        Ok(collection_id)
+
+        rows.into_iter()
+            .map(|row| {
+                let operation = ChangeOperation::from_db_str(&row.operation).ok_or_else(|| {
+                    Error::with_args(
+                        ErrorKind::DB,
+                        "db-error",
+                        vec![(
+                            "detail",
+                            format!("unrecognized change operation {:?}", row.operation),
+                        )],
+                    )
+                })?;
+                Ok(Change {
+                    seq: row.seq,
+                    parent_seq: row.parent_seq,
+                    host_id: row.host_id,
+                    operation,
+                    collection_id: row.collection_id,
+                    tag: row.tag,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
     }
 
-    async fn add_item_to_collection(
+    /// Insert a new tag for an item.
+    ///
+    /// Runs in its own `DB::with_transaction`, so the tag insert and the `Change` row recording it
+    /// are atomic. `import_file`/`import_file_chunked` do not call this: they tag their item through
+    /// `Tx::add_tag_to_collection` directly inside their own transaction.
+    pub async fn add_tag_to_collection(&mut self, collection_id: i64, tag: &str) -> Result<()> {
+        self.with_transaction(move |tx| Box::pin(tx.add_tag_to_collection(collection_id, tag)))
+            .await
+    }
+
+    /// Import a file into the database with an Incomplete tag.
+    ///
+    /// Runs entirely inside `DB::with_transaction`, so a duplicate `hash` rolls back the
+    /// collection row this would otherwise have left dangling rather than leaving it behind.
+    pub async fn import_file(
         &mut self,
-        collection_id: i64,
+        title: &str,
         hash: &str,
         ext: &str,
-    ) -> Result<i64> {
-        let item_id = sqlx::query!(
+        media_kind: MediaKind,
+    ) -> Result<()> {
+        self.import_item(title, hash, ext, media_kind, &[String::from("meta:Incomplete")])
+            .await
+    }
+
+    /// Shared by `import_file` and `import_json`: creates the collection/item rows for `hash` and
+    /// applies `tags` to it, all inside one `DB::with_transaction` so a duplicate `hash` leaves
+    /// nothing behind. Unlike `import_file_chunked`, this records no chunks at all, since neither
+    /// caller has any chunk bytes to hand: `import_file` predates chunking, and `import_json`
+    /// reconstructs a catalog row from a JSON snapshot that never carried chunk data in the first
+    /// place (see `export_json`).
+    async fn import_item(
+        &mut self,
+        title: &str,
+        hash: &str,
+        ext: &str,
+        media_kind: MediaKind,
+        tags: &[String],
+    ) -> Result<()> {
+        self.with_transaction(move |tx| {
+            Box::pin(async move {
+                let collection_id = tx.add_collection(title).await?;
+                tx.add_item_to_collection(
+                        collection_id,
+                        hash,
+                        ext,
+                        media_kind,
+                        &MediaMetadata::default(),
+                    )
+                    .await?;
+                tx.record_change(ChangeOperation::AddItem, Some(collection_id), None)
+                    .await?;
+                for tag in tags {
+                    tx.add_tag_to_collection(collection_id, tag).await?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Import a file into the database, tagging it with `tags`, recording the technical
+    /// `metadata` probed from it, and recording it as an ordered list of content-defined chunks
+    /// rather than a single whole-file blob.
+    ///
+    /// Chunks already present in the db (shared with another file) only have their `refcount`
+    /// bumped; new chunks are inserted with `refcount = 1`. Like `import_file`, this runs entirely
+    /// inside `DB::with_transaction` so a duplicate `hash` leaves nothing behind.
+    pub async fn import_file_chunked(
+        &mut self,
+        title: &str,
+        hash: &str,
+        ext: &str,
+        media_kind: MediaKind,
+        tags: &[String],
+        metadata: &MediaMetadata,
+        chunks: &[(String, i64)],
+    ) -> Result<()> {
+        self.with_transaction(move |tx| {
+            Box::pin(async move {
+                let collection_id = tx.add_collection(title).await?;
+                let item_id = tx
+                    .add_item_to_collection(collection_id, hash, ext, media_kind, metadata)
+                    .await?;
+                tx.record_change(ChangeOperation::AddItem, Some(collection_id), None)
+                    .await?;
+                for tag in tags {
+                    tx.add_tag_to_collection(collection_id, tag).await?;
+                }
+                for (index, (chunk_hash, size)) in chunks.iter().enumerate() {
+                    tx.upsert_chunk(chunk_hash, *size).await?;
+                    tx.link_item_chunk(item_id, index as i64, chunk_hash)
+                        .await?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    /// Returns the ordered list of chunk hashes making up `item_id`.
+    pub async fn get_item_chunks(&mut self, item_id: i64) -> Result<Vec<String>> {
+        let chunks = sqlx::query!(
             "
-            INSERT OR ROLLBACK INTO items(collection_id, hash, ext)
-            VALUES (?, ?, ?)
-            RETURNING item_id
+            SELECT chunk_hash FROM item_chunks
+            WHERE item_id = ?
+            ORDER BY chunk_index
             ",
-            collection_id,
-            hash,
-            ext
+            item_id
         )
-        .map(|row| row.item_id)
-        .fetch_one(&mut self.connection)
+        .map(|row| row.chunk_hash)
+        .fetch_all(&mut self.connection)
         .await?;
-        Ok(item_id)
+        Ok(chunks)
     }
 
-    /// Insert a new tag for an item.
-    pub async fn add_tag_to_collection(&mut self, collection_id: i64, tag: &str) -> Result<()> {
-        // Check if the given $name exists
-        sqlx::query!("INSERT OR IGNORE INTO tags(name) VALUES (?)", tag)
-            .execute(&mut self.connection)
+    /// Returns whether a chunk with `hash` is already known to the db.
+    pub async fn chunk_exists(&mut self, hash: &str) -> Result<bool> {
+        let count = sqlx::query!("SELECT COUNT(*) as count FROM chunks WHERE hash = ?", hash)
+            .map(|row| row.count)
+            .fetch_one(&mut self.connection)
             .await?;
-        sqlx::query!(
-            "
-            INSERT INTO collection_tag(collection_id, tag_id)
-            SELECT ?, tag_id FROM tags WHERE name=?;
-            ",
-            collection_id,
-            tag
-        )
-        .execute(&mut self.connection)
-        .await?;
+        Ok(count > 0)
+    }
+
+    /// Returns the subset of `hashes` already known to the db, in `WHERE hash IN (...)`
+    /// round-trips rather than one `chunk_exists` query per hash.
+    ///
+    /// `hashes` may be empty, in which case this returns without touching the db at all. Queried
+    /// in batches of `CHUNKS_EXIST_BATCH_SIZE` rather than one giant `IN` list so a single
+    /// large file's chunks can't exceed SQLite's bound-parameter limit.
+    pub async fn chunks_exist(&mut self, hashes: &[String]) -> Result<Vec<String>> {
+        let mut found = Vec::new();
+        for batch in hashes.chunks(CHUNKS_EXIST_BATCH_SIZE) {
+            let placeholders = vec!["?"; batch.len()].join(",");
+            let query = format!("SELECT hash FROM chunks WHERE hash IN ({placeholders})");
+            let mut query = sqlx::query(&query);
+            for hash in batch {
+                query = query.bind(hash);
+            }
+            let rows = query.fetch_all(&mut self.connection).await?;
+            for row in rows {
+                found.push(row.try_get("hash")?);
+            }
+        }
+        Ok(found)
+    }
+
+    /// Serializes every item's title, extension, hash, media kind, and tags as a JSON array,
+    /// streamed to `writer` as each item is formatted rather than built up as one giant string.
+    ///
+    /// This is a human-readable, VCS-friendly catalog snapshot, built entirely on top of
+    /// `get_items`, an operation every `Database` impl supports — unlike `Repo::export`'s binary
+    /// archive, which bundles the raw `vorg.db` file and so only supports a SQLite-backed repo
+    /// (see `archive`'s module doc). Lives on `DB` rather than the `Database` trait for now since
+    /// nothing calls it on a `PostgresDatabase` yet; promoting it there later would be a small
+    /// change. It intentionally carries no chunk data: technical
+    /// metadata (duration, codecs, ...), `imported_at`, and chunk membership are not preserved, so
+    /// this is a catalog backup rather than a byte-for-byte clone. Pair it with a
+    /// `Repo::check_data_integrity`/`repair` pass if chunk membership needs reconciling against an
+    /// already-populated store after a restore.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    /// - `ErrorKind::IO` if writing to `writer` fails.
+    pub async fn export_json<W: Write>(&mut self, mut writer: W) -> Result<()> {
+        let items = self.get_items(&Filter::new()).await?;
+        writer.write_all(b"[")?;
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                writer.write_all(b",")?;
+            }
+            writer.write_all(item_to_json(item).as_bytes())?;
+        }
+        writer.write_all(b"]")?;
         Ok(())
     }
 
-    /// Import a file into the database with an Incomplete tag.
-    pub async fn import_file(&mut self, title: &str, hash: &str, ext: &str) -> Result<()> {
-        self.begin_transaction().await?;
-        // Add collection
-        let collection_id = self.add_collection(title).await?;
-        // Add item to collection
-        let Ok(item_id) = self.add_item_to_collection(collection_id, hash, ext).await else {
-            return Err(Error {
-                msg: String::from("The item to import already exists in the database."),
-                kind: ErrorKind::Duplicate,
-            });
+    /// Reloads items previously written by `export_json`, replaying each through `import_item` (the
+    /// same row-creation path `import_file` uses) with its own tags preserved rather than forcing
+    /// the usual placeholder `"meta:Incomplete"`.
+    ///
+    /// Tolerates object fields this version doesn't recognize, for forward compatibility with a
+    /// future export format. An item whose `hash` already exists in this db is skipped rather than
+    /// erroring, so replaying the same snapshot twice (or into a partially-restored db) is safe.
+    ///
+    /// # Errors
+    /// - `ErrorKind::InvalidJson` if `reader` is not well-formed JSON, is not a JSON array, any
+    ///   entry is missing `title`/`ext`/`hash`/`media_kind`, has an unrecognized `media_kind`, or
+    ///   `hash` isn't `ITEM_HASH_HEX_LEN` lowercase hex digits (see `validate_item_hash`).
+    /// - `ErrorKind::DB` if the underlying query fails.
+    /// - `ErrorKind::IO` if reading from `reader` fails.
+    pub async fn import_json<R: Read>(&mut self, mut reader: R) -> Result<()> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        let items = parse_json_items(&input)?;
+        for item in items {
+            let media_kind = MediaKind::from_db_str(&item.media_kind)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidJson, "json-invalid"))?;
+            let result = self
+                .import_item(&item.title, &item.hash, &item.ext, media_kind, &item.tags)
+                .await;
+            if let Err(error) = result {
+                if error.kind != ErrorKind::Duplicate {
+                    return Err(error);
+                }
+                eprintln!("Skipping duplicate item {} already in catalog.", item.hash);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns all known chunk hashes, sorted ascending, for use by `check_data_integrity`.
+    pub async fn get_all_chunk_hashes(&mut self) -> Result<Vec<String>> {
+        let hashes = sqlx::query!("SELECT hash FROM chunks ORDER BY hash")
+            .map(|row| row.hash)
+            .fetch_all(&mut self.connection)
+            .await?;
+        Ok(hashes)
+    }
+
+    /// Returns all item hashes, for cross-referencing against generated thumbnails in
+    /// `check_data_integrity`.
+    pub async fn get_all_item_hashes(&mut self) -> Result<Vec<String>> {
+        let hashes = sqlx::query!("SELECT hash FROM items ORDER BY hash")
+            .map(|row| row.hash)
+            .fetch_all(&mut self.connection)
+            .await?;
+        Ok(hashes)
+    }
+
+    /// Returns the chunk hashes making up the item identified by `hash`, in original chunking
+    /// order, or `None` if no such item exists.
+    pub async fn get_item_chunk_hashes(&mut self, hash: &str) -> Result<Option<Vec<String>>> {
+        let item_id = sqlx::query!("SELECT item_id FROM items WHERE hash = ?", hash)
+            .map(|row| row.item_id)
+            .fetch_optional(&mut self.connection)
+            .await?;
+        let Some(item_id) = item_id else {
+            return Ok(None);
         };
-        // Add tag
-        self.add_tag_to_collection(item_id, "meta:Incomplete")
+        let chunk_hashes = sqlx::query!(
+            "SELECT chunk_hash FROM item_chunks WHERE item_id = ? ORDER BY chunk_index",
+            item_id
+        )
+        .map(|row| row.chunk_hash)
+        .fetch_all(&mut self.connection)
+        .await?;
+        Ok(Some(chunk_hashes))
+    }
+
+    /// Updates the extension recorded for the item identified by `hash`.
+    pub async fn update_item_extension(&mut self, hash: &str, ext: &str) -> Result<()> {
+        sqlx::query!("UPDATE items SET ext = ? WHERE hash = ?", ext, hash)
+            .execute(&mut self.connection)
             .await?;
-        self.commit_transaction().await?;
         Ok(())
     }
 
-    /// Get files that satisfy the given filter.
+    /// Get files that satisfy `filter`.
     ///
-    /// TODO: Add filtering.
-    pub async fn get_items(&mut self) -> Result<Vec<Item>> {
-        // Access items table
-        let items_query = "
-        SELECT hash, title, ext, c.collection_id
-        FROM collections c
-        JOIN items i ON c.collection_id = i.collection_id
-        ORDER BY hash
-        ";
-        let mut items = sqlx::query_as::<_, Item>(items_query)
-            .fetch_all(&mut self.connection)
-            .await?;
+    /// Tag and title conditions are pushed down into the `WHERE` clause below rather than applied
+    /// in memory, so a tag filter on a large repo stays a single indexed query.
+    pub async fn get_items(&mut self, filter: &Filter) -> Result<Vec<Item>> {
+        let mut items_query = String::from(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate
+            FROM collections c
+            JOIN items i ON c.collection_id = i.collection_id
+            WHERE 1 = 1
+            ",
+        );
+        DB::push_filter_predicates(&mut items_query, filter);
+        items_query.push_str(" ORDER BY hash");
+
+        let mut query = sqlx::query_as::<_, Item>(&items_query);
+        for tag in &filter.include_tags {
+            query = query.bind(tag);
+        }
+        for tag in &filter.exclude_tags {
+            query = query.bind(tag);
+        }
+        if let Some(substring) = &filter.title_contains {
+            query = query.bind(format!("%{substring}%"));
+        }
+        if let Some(media_kind) = filter.media_kind {
+            query = query.bind(media_kind.as_db_str());
+        }
+        if let Some(extension) = &filter.extension {
+            query = query.bind(extension);
+        }
+        let mut items = query.fetch_all(&mut self.connection).await?;
 
         for item in items.iter_mut() {
             let tags = sqlx::query!(
@@ -504,292 +789,2709 @@ impl DB {
 
         Ok(items)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rstest::rstest;
-    use test_context::{test_context, AsyncTestContext};
-    use tokio::time::{sleep, Duration};
-    use uuid::Uuid;
 
-    struct TempFolder {
-        pub path: std::path::PathBuf,
-    }
+    /// Searches collection titles via the `title_fts` FTS5 index from migration 0 (an
+    /// external-content table over `collections.title`, kept in sync by the `title_insert`/
+    /// `title_delete`/`title_update` triggers), returning matches ranked by `bm25` relevance with
+    /// their tags populated like `get_items`.
+    ///
+    /// By default `query` is sanitized into a search for literal terms: bare terms are individually
+    /// quoted and embedded quotes escaped, so a title containing FTS5 syntax (`foo "bar" AND
+    /// baz`) is matched literally instead of throwing a query syntax error. Pass
+    /// `raw_fts_syntax: true` to skip sanitizing and hand `query` to FTS5's query grammar
+    /// unchanged, for power users who want `column:term`/`AND`/`OR`/`NOT`/prefix (`term*`)
+    /// queries.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails, e.g. `query` is malformed FTS5 syntax
+    ///   under `raw_fts_syntax`.
+    pub async fn search_collections(
+        &mut self,
+        query: &str,
+        raw_fts_syntax: bool,
+    ) -> Result<Vec<Item>> {
+        let fts_query = if raw_fts_syntax {
+            query.to_string()
+        } else {
+            sanitize_fts_query(query)
+        };
 
-    #[async_trait::async_trait]
-    impl AsyncTestContext for TempFolder {
-        async fn setup() -> TempFolder {
-            let uuid = Uuid::new_v4();
-            let temp_dir_path =
-                String::from("temp-") + uuid.hyphenated().encode_lower(&mut Uuid::encode_buffer());
-            let temp_dir = std::path::PathBuf::from(temp_dir_path);
-            fs::create_dir(&temp_dir).expect("Failed to create temp dir for testing.");
-            TempFolder { path: temp_dir }
-        }
+        let mut items = sqlx::query_as::<_, Item>(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate
+            FROM title_fts
+            JOIN collections c ON c.collection_id = title_fts.rowid
+            JOIN items i ON c.collection_id = i.collection_id
+            WHERE title_fts MATCH ?
+            ORDER BY bm25(title_fts)
+            ",
+        )
+        .bind(fts_query)
+        .fetch_all(&mut self.connection)
+        .await?;
 
-        async fn teardown(self) {
-            if let Err(_) = fs::remove_dir_all(&self.path) {
-                // If the first try failed, wait a bit and retry
-                sleep(Duration::from_millis(200)).await;
-                fs::remove_dir_all(&self.path).expect("Failed to teardown temp test directory.")
+        for item in items.iter_mut() {
+            let tags = sqlx::query!(
+                "
+                SELECT name FROM tags t
+                JOIN collection_tag ct
+                ON ct.tag_id = t.tag_id
+                JOIN collections c
+                ON c.collection_id = ct.collection_id
+                WHERE c.collection_id = ?
+                ",
+                item.collection_id
+            )
+            .map(|row| row.name)
+            .fetch_all(&mut self.connection)
+            .await?;
+            item.tags = tags;
+        }
+
+        Ok(items)
+    }
+
+    /// Returns items matching `filter`, a `query::FilterExpr` boolean expression over tags and
+    /// titles. An empty filter (`FilterExpr::And(vec![])`, what `query::parse("")` produces)
+    /// degenerates to "match everything", the same as `get_items(&Filter::new())`.
+    ///
+    /// Unlike `Filter`/`get_items`, which can only AND conditions together, `FilterExpr` can
+    /// express `OR` and `NOT`. `DB::compile_filter_expr` compiles it to a parameterized query over
+    /// `collection_id`, combining each leaf's subquery with `INTERSECT`/`UNION`/`EXCEPT`; see that
+    /// function for why each compiled fragment is wrapped in `FROM (...)`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    pub async fn query_items(&mut self, filter: &FilterExpr) -> Result<Vec<Item>> {
+        let mut binds = Vec::new();
+        let matched_ids_sql = DB::compile_filter_expr(filter, &mut binds);
+
+        let items_query = format!(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate
+            FROM ({matched_ids_sql}) matched
+            JOIN collections c ON c.collection_id = matched.collection_id
+            JOIN items i ON c.collection_id = i.collection_id
+            ORDER BY hash
+            "
+        );
+        let mut query = sqlx::query_as::<_, Item>(&items_query);
+        for bind in &binds {
+            query = query.bind(bind);
+        }
+        let mut items = query.fetch_all(&mut self.connection).await?;
+
+        for item in items.iter_mut() {
+            let tags = sqlx::query!(
+                "
+                SELECT name FROM tags t
+                JOIN collection_tag ct
+                ON ct.tag_id = t.tag_id
+                JOIN collections c
+                ON c.collection_id = ct.collection_id
+                WHERE c.collection_id = ?
+                ",
+                item.collection_id
+            )
+            .map(|row| row.name)
+            .fetch_all(&mut self.connection)
+            .await?;
+            item.tags = tags;
+        }
+
+        Ok(items)
+    }
+
+    /// Like `DB::query_items`, but paginated via plain `LIMIT`/`OFFSET` and reporting the total
+    /// number of matches alongside the page, for "page N of M" style UIs over a tag/title query.
+    ///
+    /// Deliberately not cursor-based like `list_items_page`: `pagination`'s module doc explains
+    /// why seeking beats limit/offset for that listing (a large, frequently-mutated catalog scan),
+    /// but a `FilterExpr`-matched result set is usually much smaller and queried once to render a
+    /// single page, so the risk of a concurrent insert/delete shifting a page is an acceptable
+    /// trade for reporting `total_count` up front — a seek cursor can't give a caller that
+    /// without its own separate `COUNT(*)` pass anyway.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    pub async fn query_items_page(
+        &mut self,
+        filter: &FilterExpr,
+        limit: usize,
+        offset: usize,
+    ) -> Result<QueryPage> {
+        let mut binds = Vec::new();
+        let matched_ids_sql = DB::compile_filter_expr(filter, &mut binds);
+
+        let count_sql = format!("SELECT COUNT(*) FROM ({matched_ids_sql}) matched");
+        let mut count_query = sqlx::query_scalar::<_, i64>(&count_sql);
+        for bind in &binds {
+            count_query = count_query.bind(bind);
+        }
+        let total_count = count_query.fetch_one(&mut self.connection).await?;
+
+        let items_query = format!(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate
+            FROM ({matched_ids_sql}) matched
+            JOIN collections c ON c.collection_id = matched.collection_id
+            JOIN items i ON c.collection_id = i.collection_id
+            ORDER BY hash
+            LIMIT ? OFFSET ?
+            "
+        );
+        let mut query = sqlx::query_as::<_, Item>(&items_query);
+        for bind in &binds {
+            query = query.bind(bind);
+        }
+        let mut items = query
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&mut self.connection)
+            .await?;
+
+        for item in items.iter_mut() {
+            let tags = sqlx::query!(
+                "
+                SELECT name FROM tags t
+                JOIN collection_tag ct
+                ON ct.tag_id = t.tag_id
+                JOIN collections c
+                ON c.collection_id = ct.collection_id
+                WHERE c.collection_id = ?
+                ",
+                item.collection_id
+            )
+            .map(|row| row.name)
+            .fetch_all(&mut self.connection)
+            .await?;
+            item.tags = tags;
+        }
+
+        Ok(QueryPage { items, total_count: total_count as usize })
+    }
+
+    /// Compiles `expr` to a `SELECT collection_id FROM ...` query, pushing any bind values it
+    /// needs (in left-to-right leaf order) onto `binds`.
+    ///
+    /// Each leaf is its own subquery over `collection_id`; `And`/`Or`/`Not` combine their operands'
+    /// subqueries with SQLite's `INTERSECT`/`UNION`/`EXCEPT` compound-select operators rather than
+    /// joining or building a single `WHERE` clause, since those operators are also how `Not` can
+    /// express "everything except this" without knowing what its operand's predicate was.
+    fn compile_filter_expr(expr: &FilterExpr, binds: &mut Vec<String>) -> String {
+        match expr {
+            FilterExpr::And(exprs) => DB::compile_compound(exprs, "INTERSECT", binds, true),
+            FilterExpr::Or(exprs) => DB::compile_compound(exprs, "UNION", binds, false),
+            FilterExpr::Not(inner) => {
+                let inner_sql = DB::wrap_for_grouping(DB::compile_filter_expr(inner, binds));
+                format!("SELECT collection_id FROM collections EXCEPT {inner_sql}")
+            }
+            FilterExpr::Tag(name) => {
+                binds.push(name.clone());
+                String::from(
+                    "
+                    SELECT ct.collection_id FROM collection_tag ct
+                    JOIN tags t ON t.tag_id = ct.tag_id
+                    WHERE t.name = ?
+                    ",
+                )
+            }
+            FilterExpr::TagPrefix(prefix) => {
+                binds.push(format!("{prefix}%"));
+                String::from(
+                    "
+                    SELECT ct.collection_id FROM collection_tag ct
+                    JOIN tags t ON t.tag_id = ct.tag_id
+                    WHERE t.name LIKE ?
+                    ",
+                )
+            }
+            FilterExpr::Title(text) => {
+                binds.push(sanitize_fts_query(text));
+                String::from(
+                    "
+                    SELECT c.collection_id FROM title_fts
+                    JOIN collections c ON c.collection_id = title_fts.rowid
+                    WHERE title_fts MATCH ?
+                    ",
+                )
+            }
+        }
+    }
+
+    /// Combines `exprs`' compiled subqueries with `operator` (`INTERSECT` for `And`, `UNION` for
+    /// `Or`). An empty `exprs` (only reachable via the top-level `FilterExpr::And(vec![])` that an
+    /// empty query string parses to) degenerates to "match everything" for `And` or "match
+    /// nothing" for `Or`, per `empty_is_all`.
+    fn compile_compound(
+        exprs: &[FilterExpr],
+        operator: &str,
+        binds: &mut Vec<String>,
+        empty_is_all: bool,
+    ) -> String {
+        if exprs.is_empty() {
+            return if empty_is_all {
+                String::from("SELECT collection_id FROM collections")
+            } else {
+                String::from("SELECT collection_id FROM collections WHERE 0 = 1")
+            };
+        }
+        exprs
+            .iter()
+            .map(|expr| DB::wrap_for_grouping(DB::compile_filter_expr(expr, binds)))
+            .collect::<Vec<_>>()
+            .join(&format!(" {operator} "))
+    }
+
+    /// Wraps a compiled subquery in `FROM (...)` before it takes part in a compound select.
+    /// SQLite's compound-select grammar rejects a parenthesized operand directly (`(SELECT ...)
+    /// INTERSECT (SELECT ...)` is a syntax error), but a subquery inside a `FROM` clause is always
+    /// parenthesizable, so this sidesteps the restriction while keeping each operand's own
+    /// precedence intact.
+    fn wrap_for_grouping(sql: String) -> String {
+        format!("SELECT collection_id FROM ({sql})")
+    }
+
+    /// Appends `filter`'s conditions, as `?`-placeholder SQL, to a `WHERE 1 = 1` query already
+    /// joining `collections c` to `items i`. Binds are expected in the same order this pushes
+    /// predicates in: include tags, exclude tags, title, media kind, extension; see `get_items`
+    /// and `list_items_page` for the matching bind sequence.
+    fn push_filter_predicates(query: &mut String, filter: &Filter) {
+        for _ in &filter.include_tags {
+            query.push_str(
+                "
+                AND EXISTS (
+                    SELECT 1 FROM collection_tag ct
+                    JOIN tags t ON t.tag_id = ct.tag_id
+                    WHERE ct.collection_id = c.collection_id AND t.name = ?
+                )
+                ",
+            );
+        }
+        for _ in &filter.exclude_tags {
+            query.push_str(
+                "
+                AND NOT EXISTS (
+                    SELECT 1 FROM collection_tag ct
+                    JOIN tags t ON t.tag_id = ct.tag_id
+                    WHERE ct.collection_id = c.collection_id AND t.name = ?
+                )
+                ",
+            );
+        }
+        if filter.title_contains.is_some() {
+            query.push_str(" AND title LIKE ?");
+        }
+        if filter.media_kind.is_some() {
+            query.push_str(" AND media_kind = ?");
+        }
+        if filter.extension.is_some() {
+            query.push_str(" AND ext = ?");
+        }
+    }
+
+    /// Returns the column name and ordering clause `list_items_page` sorts/seeks by for `order`.
+    fn order_column_and_direction(order: ItemOrder) -> (&'static str, &'static str) {
+        match order {
+            ItemOrder::NewestImported => ("imported_at", "DESC"),
+            ItemOrder::OldestImported => ("imported_at", "ASC"),
+            ItemOrder::TitleAscending => ("title", "ASC"),
+            ItemOrder::TitleDescending => ("title", "DESC"),
+            ItemOrder::SmallestFirst => ("total_size", "ASC"),
+            ItemOrder::LargestFirst => ("total_size", "DESC"),
+        }
+    }
+
+    /// Returns one page of items that satisfy `filter`, ordered by `order`, seeking past `cursor`.
+    /// See `pagination` for the design and `Database::list_items_page` for the contract.
+    pub async fn list_items_page(
+        &mut self,
+        filter: &Filter,
+        order: ItemOrder,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<ItemPage> {
+        let (sort_column, direction) = DB::order_column_and_direction(order);
+        let op = if direction == "ASC" { ">" } else { "<" };
+
+        let mut inner_query = String::from(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate,
+                (
+                    SELECT COALESCE(SUM(ch.size), 0) FROM item_chunks ic
+                    JOIN chunks ch ON ch.hash = ic.chunk_hash
+                    WHERE ic.item_id = i.item_id
+                ) AS total_size
+            FROM collections c
+            JOIN items i ON c.collection_id = i.collection_id
+            WHERE 1 = 1
+            ",
+        );
+        DB::push_filter_predicates(&mut inner_query, filter);
+
+        let page_query = format!(
+            "SELECT * FROM ({inner_query}) t WHERE 1 = 1
+             AND (? OR {sort_column} {op} ? OR ({sort_column} = ? AND hash {op} ?))
+             ORDER BY {sort_column} {direction}, hash {direction}
+             LIMIT ?"
+        );
+        // The leading bound boolean lets one query string serve both the first page (no cursor:
+        // bind true there and dummy values for the rest) and later pages, rather than building two
+        // different SQL strings depending on whether a cursor was given.
+        let mut query = sqlx::query(&page_query);
+        for tag in &filter.include_tags {
+            query = query.bind(tag);
+        }
+        for tag in &filter.exclude_tags {
+            query = query.bind(tag);
+        }
+        if let Some(substring) = &filter.title_contains {
+            query = query.bind(format!("%{substring}%"));
+        }
+        if let Some(media_kind) = filter.media_kind {
+            query = query.bind(media_kind.as_db_str());
+        }
+        if let Some(extension) = &filter.extension {
+            query = query.bind(extension);
+        }
+
+        let cursor_invalid = || Error::new(ErrorKind::InvalidCursor, "pagination-cursor-invalid");
+        let (cursor_is_none, cursor_key_numeric, cursor_key_text, cursor_hash) = match cursor {
+            None => (true, 0_i64, String::new(), String::new()),
+            Some(token) => {
+                let (key, hash) = pagination::decode_cursor(token)?;
+                match order {
+                    ItemOrder::TitleAscending | ItemOrder::TitleDescending => {
+                        (false, 0_i64, key, hash)
+                    }
+                    ItemOrder::NewestImported
+                    | ItemOrder::OldestImported
+                    | ItemOrder::SmallestFirst
+                    | ItemOrder::LargestFirst => {
+                        let numeric: i64 = key.parse().map_err(|_| cursor_invalid())?;
+                        (false, numeric, String::new(), hash)
+                    }
+                }
+            }
+        };
+        query = query.bind(cursor_is_none);
+        query = match order {
+            ItemOrder::TitleAscending | ItemOrder::TitleDescending => query
+                .bind(cursor_key_text.clone())
+                .bind(cursor_key_text.clone()),
+            ItemOrder::NewestImported
+            | ItemOrder::OldestImported
+            | ItemOrder::SmallestFirst
+            | ItemOrder::LargestFirst => query.bind(cursor_key_numeric).bind(cursor_key_numeric),
+        };
+        query = query.bind(cursor_hash).bind(page_size as i64 + 1);
+
+        let rows = query.fetch_all(&mut self.connection).await?;
+
+        let fetched_extra = rows.len() > page_size;
+        let mut items: Vec<Item> = Vec::with_capacity(page_size.min(rows.len()));
+        let mut sort_keys: Vec<(String, String)> = Vec::with_capacity(page_size.min(rows.len()));
+        for row in rows.iter().take(page_size) {
+            items.push(Item::from_row(row)?);
+            let hash: String = row.try_get("hash")?;
+            let key = match order {
+                ItemOrder::NewestImported | ItemOrder::OldestImported => {
+                    let imported_at: i64 = row.try_get("imported_at")?;
+                    imported_at.to_string()
+                }
+                ItemOrder::TitleAscending | ItemOrder::TitleDescending => {
+                    let title: String = row.try_get("title")?;
+                    title
+                }
+                ItemOrder::SmallestFirst | ItemOrder::LargestFirst => {
+                    let total_size: i64 = row.try_get("total_size")?;
+                    total_size.to_string()
+                }
             };
+            sort_keys.push((key, hash));
+        }
+
+        for item in items.iter_mut() {
+            let tags = sqlx::query!(
+                "
+                SELECT name FROM tags t
+                JOIN collection_tag ct
+                ON ct.tag_id = t.tag_id
+                JOIN collections c
+                ON c.collection_id = ct.collection_id
+                WHERE c.collection_id = ?
+                ",
+                item.collection_id
+            )
+            .map(|row| row.name)
+            .fetch_all(&mut self.connection)
+            .await?;
+            item.tags = tags;
+        }
+
+        let next_cursor = fetched_extra
+            .then(|| sort_keys.last())
+            .flatten()
+            .map(|(key, hash)| pagination::encode_cursor(key, hash));
+
+        Ok(ItemPage::new(items, next_cursor))
+    }
+
+    /// Deletes the item identified by `hash`, along with its collection and tags, releasing its
+    /// chunks via `Tx::release_item_chunks`.
+    ///
+    /// Returns the hashes of any chunks whose `refcount` dropped to zero as a result; see
+    /// `Database::delete_item` for why the caller, not this method, is responsible for unlinking
+    /// the corresponding blobs.
+    pub async fn delete_item(&mut self, hash: &str) -> Result<Vec<String>> {
+        self.with_transaction(move |tx| {
+            Box::pin(async move {
+                let item = sqlx::query!(
+                    "SELECT item_id, collection_id FROM items WHERE hash = ?",
+                    hash
+                )
+                .fetch_optional(&mut tx.connection)
+                .await?;
+                let Some(item) = item else {
+                    return Ok(Vec::new());
+                };
+                let reclaimed_chunks = tx.release_item_chunks(item.item_id).await?;
+                sqlx::query!("DELETE FROM items WHERE collection_id = ?", item.collection_id)
+                    .execute(&mut tx.connection)
+                    .await?;
+                sqlx::query!(
+                    "DELETE FROM collection_tag WHERE collection_id = ?",
+                    item.collection_id
+                )
+                .execute(&mut tx.connection)
+                .await?;
+                sqlx::query!(
+                    "DELETE FROM collections WHERE collection_id = ?",
+                    item.collection_id
+                )
+                .execute(&mut tx.connection)
+                .await?;
+                tx.record_change(ChangeOperation::RemoveItem, Some(item.collection_id), None)
+                    .await?;
+                Ok(reclaimed_chunks)
+            })
+        })
+        .await
+    }
+}
+
+/// A transaction-scoped handle passed into `DB::with_transaction`'s closure. Write helpers that
+/// need to run inside a transaction (`add_collection`, `add_item_to_collection`,
+/// `add_tag_to_collection`, `upsert_chunk`, `link_item_chunk`, `release_item_chunks`, `host_id`,
+/// `record_change`) live here, executed against `self.connection` (a `sqlx::Transaction`) instead
+/// of `DB`'s dedicated connection. `host_id` and `add_tag_to_collection` also have a `DB`
+/// counterpart; for those this is the same duplicate-rather-than-abstract approach `ReadDb` takes
+/// for its read methods.
+///
+/// `Tx` only exposes `with_transaction` and these write helpers, not `DB::with_transaction`
+/// itself, so a closure cannot reach back out to `self` and commit or roll back out of band.
+pub(crate) struct Tx<'c> {
+    connection: sqlx::Transaction<'c, Sqlite>,
+}
+
+impl<'c> Tx<'c> {
+    /// Runs `f` inside a nested transaction scoped to this one. Since `self.connection` is already
+    /// an open `sqlx::Transaction`, beginning another transaction on it is automatically recorded
+    /// by sqlx as a SQLite `SAVEPOINT` rather than a second `BEGIN`, and committing/rolling it back
+    /// becomes `RELEASE SAVEPOINT`/`ROLLBACK TO SAVEPOINT` instead of ending the outer transaction.
+    async fn with_transaction<F, T>(&mut self, f: F) -> Result<T>
+    where
+        F: for<'t> FnOnce(&'t mut Tx<'_>) -> BoxFuture<'t, Result<T>>,
+    {
+        let mut nested = Tx {
+            connection: self.connection.begin().await?,
+        };
+        match f(&mut nested).await {
+            Ok(value) => {
+                nested.connection.commit().await?;
+                Ok(value)
+            }
+            Err(err) => {
+                nested.connection.rollback().await?;
+                Err(err)
+            }
         }
     }
 
+    /// Adds a new collection.
+    async fn add_collection(&mut self, title: &str) -> Result<i64> {
+        let collection_id = sqlx::query!(
+            "
+            INSERT INTO collections(title) VALUES(?)
+            RETURNING collection_id;
+            ",
+            title
+        )
+        .map(|row| row.collection_id)
+        .fetch_one(&mut self.connection)
+        .await?;
+        Ok(collection_id)
+    }
+
+    /// Plain `INSERT`, not `INSERT OR ROLLBACK`: SQLite's `OR ROLLBACK` conflict resolution rolls
+    /// back and closes the *entire* enclosing transaction the instant the `hash` unique index
+    /// conflicts, before this function even gets to return — so by the time `with_transaction`'s
+    /// `Err` branch tries its own `ROLLBACK`, there is no transaction left to roll back, and that
+    /// failure (not the real `Duplicate` error) is what callers would see. A conflict here instead
+    /// surfaces as an ordinary `sqlx::Error`, classified below and left for `with_transaction` to
+    /// roll back normally.
+    async fn add_item_to_collection(
+        &mut self,
+        collection_id: i64,
+        hash: &str,
+        ext: &str,
+        media_kind: MediaKind,
+        metadata: &MediaMetadata,
+    ) -> Result<i64> {
+        let media_kind = media_kind.as_db_str();
+        let imported_at = now_unix_timestamp();
+        let item_id = sqlx::query!(
+            "
+            INSERT INTO items(
+                collection_id, hash, ext, media_kind,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate, imported_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING item_id
+            ",
+            collection_id,
+            hash,
+            ext,
+            media_kind,
+            metadata.duration,
+            metadata.width,
+            metadata.height,
+            metadata.frame_rate,
+            metadata.container,
+            metadata.video_codec,
+            metadata.audio_codec,
+            metadata.bitrate,
+            imported_at,
+        )
+        .map(|row| row.item_id)
+        .fetch_one(&mut self.connection)
+        .await
+        .map_err(|error| {
+            if error
+                .as_database_error()
+                .is_some_and(sqlx::error::DatabaseError::is_unique_violation)
+            {
+                Error::new(ErrorKind::Duplicate, "duplicate")
+            } else {
+                Error::from(error)
+            }
+        })?;
+        Ok(item_id)
+    }
+
+    /// Insert a new tag for an item.
+    async fn add_tag_to_collection(&mut self, collection_id: i64, tag: &str) -> Result<()> {
+        sqlx::query!("INSERT OR IGNORE INTO tags(name) VALUES (?)", tag)
+            .execute(&mut self.connection)
+            .await?;
+        sqlx::query!(
+            "
+            INSERT INTO collection_tag(collection_id, tag_id)
+            SELECT ?, tag_id FROM tags WHERE name=?;
+            ",
+            collection_id,
+            tag
+        )
+        .execute(&mut self.connection)
+        .await?;
+        self.record_change(ChangeOperation::AddTag, Some(collection_id), Some(tag))
+            .await?;
+        Ok(())
+    }
+
+    /// Inserts a chunk if it is not already known, otherwise bumps its `refcount`.
+    async fn upsert_chunk(&mut self, hash: &str, size: i64) -> Result<()> {
+        sqlx::query!(
+            "
+            INSERT INTO chunks(hash, size, refcount) VALUES (?, ?, 1)
+            ON CONFLICT(hash) DO UPDATE SET refcount = refcount + 1
+            ",
+            hash,
+            size
+        )
+        .execute(&mut self.connection)
+        .await?;
+        Ok(())
+    }
+
+    /// Records that `item_id`'s chunk at position `index` is `chunk_hash`.
+    async fn link_item_chunk(&mut self, item_id: i64, index: i64, chunk_hash: &str) -> Result<()> {
+        sqlx::query!(
+            "
+            INSERT INTO item_chunks(item_id, chunk_index, chunk_hash) VALUES (?, ?, ?)
+            ",
+            item_id,
+            index,
+            chunk_hash
+        )
+        .execute(&mut self.connection)
+        .await?;
+        Ok(())
+    }
+
+    /// Decrements the `refcount` of every chunk `item_id` references, deleting the `chunks` row
+    /// (and, either way, the `item_chunks` links) for any that drops to zero, and returns those
+    /// now-unreferenced hashes. The caller still owns the `Store` those hashes' blobs live in, so
+    /// it deletes the files themselves once this transaction has committed.
+    async fn release_item_chunks(&mut self, item_id: i64) -> Result<Vec<String>> {
+        let chunk_hashes = sqlx::query!(
+            "SELECT chunk_hash FROM item_chunks WHERE item_id = ?",
+            item_id
+        )
+        .map(|row| row.chunk_hash)
+        .fetch_all(&mut self.connection)
+        .await?;
+
+        let mut reclaimed = Vec::new();
+        for chunk_hash in &chunk_hashes {
+            let refcount = sqlx::query!(
+                "UPDATE chunks SET refcount = refcount - 1 WHERE hash = ? RETURNING refcount",
+                chunk_hash
+            )
+            .map(|row| row.refcount)
+            .fetch_one(&mut self.connection)
+            .await?;
+            if refcount <= 0 {
+                sqlx::query!("DELETE FROM chunks WHERE hash = ?", chunk_hash)
+                    .execute(&mut self.connection)
+                    .await?;
+                reclaimed.push(chunk_hash.clone());
+            }
+        }
+
+        sqlx::query!("DELETE FROM item_chunks WHERE item_id = ?", item_id)
+            .execute(&mut self.connection)
+            .await?;
+
+        Ok(reclaimed)
+    }
+
+    /// Like `DB::host_id`.
+    async fn host_id(&mut self) -> Result<String> {
+        let existing = sqlx::query!("SELECT value FROM meta WHERE key = 'host_id'")
+            .map(|row| row.value)
+            .fetch_optional(&mut self.connection)
+            .await?;
+        if let Some(host_id) = existing {
+            return Ok(host_id);
+        }
+
+        let host_id = generate_host_id();
+        sqlx::query!(
+            "INSERT INTO meta(key, value) VALUES ('host_id', ?)",
+            host_id
+        )
+        .execute(&mut self.connection)
+        .await?;
+        Ok(host_id)
+    }
+
+    /// Appends one row to the append-only `changes` log (see `history`) and returns its `seq`.
+    /// Always called from within the transaction the caller is already inside, so the
+    /// change row and the data mutation it records can never drift: either both commit or both
+    /// roll back together. `parent_seq`/`seq` are likewise read and assigned in that transaction,
+    /// which is what keeps the chain gap-free — `DB` holds the single dedicated write connection,
+    /// so no other writer can interleave an insert between the `SELECT MAX(seq)` below and this
+    /// one.
+    async fn record_change(
+        &mut self,
+        operation: ChangeOperation,
+        collection_id: Option<i64>,
+        tag: Option<&str>,
+    ) -> Result<i64> {
+        let host_id = self.host_id().await?;
+        let parent_seq: Option<i64> = sqlx::query_scalar("SELECT MAX(seq) FROM changes")
+            .fetch_one(&mut self.connection)
+            .await?;
+        let seq = parent_seq.unwrap_or(0) + 1;
+        let operation_str = operation.as_db_str();
+        let created_at = now_unix_timestamp();
+        sqlx::query!(
+            "
+            INSERT INTO changes(seq, parent_seq, host_id, operation, collection_id, tag, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ",
+            seq,
+            parent_seq,
+            host_id,
+            operation_str,
+            collection_id,
+            tag,
+            created_at,
+        )
+        .execute(&mut self.connection)
+        .await?;
+        Ok(seq)
+    }
+}
+
+impl ReadDb {
+    /// Like `DB::get_items`, but checks out one of this handle's pooled connections rather than
+    /// serializing on a single dedicated one.
+    ///
+    /// # Errors
+    /// Same as `DB::get_items`.
+    pub async fn get_items(&self, filter: &Filter) -> Result<Vec<Item>> {
+        let mut items_query = String::from(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate
+            FROM collections c
+            JOIN items i ON c.collection_id = i.collection_id
+            WHERE 1 = 1
+            ",
+        );
+        DB::push_filter_predicates(&mut items_query, filter);
+        items_query.push_str(" ORDER BY hash");
+
+        let mut query = sqlx::query_as::<_, Item>(&items_query);
+        for tag in &filter.include_tags {
+            query = query.bind(tag);
+        }
+        for tag in &filter.exclude_tags {
+            query = query.bind(tag);
+        }
+        if let Some(substring) = &filter.title_contains {
+            query = query.bind(format!("%{substring}%"));
+        }
+        if let Some(media_kind) = filter.media_kind {
+            query = query.bind(media_kind.as_db_str());
+        }
+        if let Some(extension) = &filter.extension {
+            query = query.bind(extension);
+        }
+        let mut items = query.fetch_all(&self.pool).await?;
+
+        for item in items.iter_mut() {
+            item.tags = self.tags_for_collection(item.collection_id).await?;
+        }
+
+        Ok(items)
+    }
+
+    /// Like `DB::search_collections`.
+    ///
+    /// # Errors
+    /// Same as `DB::search_collections`.
+    pub async fn search_collections(&self, query: &str, raw_fts_syntax: bool) -> Result<Vec<Item>> {
+        let fts_query = if raw_fts_syntax {
+            query.to_string()
+        } else {
+            sanitize_fts_query(query)
+        };
+
+        let mut items = sqlx::query_as::<_, Item>(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate
+            FROM title_fts
+            JOIN collections c ON c.collection_id = title_fts.rowid
+            JOIN items i ON c.collection_id = i.collection_id
+            WHERE title_fts MATCH ?
+            ORDER BY bm25(title_fts)
+            ",
+        )
+        .bind(fts_query)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for item in items.iter_mut() {
+            item.tags = self.tags_for_collection(item.collection_id).await?;
+        }
+
+        Ok(items)
+    }
+
+    /// Like `DB::query_items`.
+    ///
+    /// # Errors
+    /// Same as `DB::query_items`.
+    pub async fn query_items(&self, filter: &FilterExpr) -> Result<Vec<Item>> {
+        let mut binds = Vec::new();
+        let matched_ids_sql = DB::compile_filter_expr(filter, &mut binds);
+
+        let items_query = format!(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate
+            FROM ({matched_ids_sql}) matched
+            JOIN collections c ON c.collection_id = matched.collection_id
+            JOIN items i ON c.collection_id = i.collection_id
+            ORDER BY hash
+            "
+        );
+        let mut query = sqlx::query_as::<_, Item>(&items_query);
+        for bind in &binds {
+            query = query.bind(bind);
+        }
+        let mut items = query.fetch_all(&self.pool).await?;
+
+        for item in items.iter_mut() {
+            item.tags = self.tags_for_collection(item.collection_id).await?;
+        }
+
+        Ok(items)
+    }
+
+    /// Like `DB::query_items_page`.
+    ///
+    /// # Errors
+    /// Same as `DB::query_items_page`.
+    pub async fn query_items_page(
+        &self,
+        filter: &FilterExpr,
+        limit: usize,
+        offset: usize,
+    ) -> Result<QueryPage> {
+        let mut binds = Vec::new();
+        let matched_ids_sql = DB::compile_filter_expr(filter, &mut binds);
+
+        let count_sql = format!("SELECT COUNT(*) FROM ({matched_ids_sql}) matched");
+        let mut count_query = sqlx::query_scalar::<_, i64>(&count_sql);
+        for bind in &binds {
+            count_query = count_query.bind(bind);
+        }
+        let total_count = count_query.fetch_one(&self.pool).await?;
+
+        let items_query = format!(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate
+            FROM ({matched_ids_sql}) matched
+            JOIN collections c ON c.collection_id = matched.collection_id
+            JOIN items i ON c.collection_id = i.collection_id
+            ORDER BY hash
+            LIMIT ? OFFSET ?
+            "
+        );
+        let mut query = sqlx::query_as::<_, Item>(&items_query);
+        for bind in &binds {
+            query = query.bind(bind);
+        }
+        let mut items =
+            query.bind(limit as i64).bind(offset as i64).fetch_all(&self.pool).await?;
+
+        for item in items.iter_mut() {
+            item.tags = self.tags_for_collection(item.collection_id).await?;
+        }
+
+        Ok(QueryPage { items, total_count: total_count as usize })
+    }
+
+    /// Like `DB::list_items_page`.
+    ///
+    /// # Errors
+    /// Same as `DB::list_items_page`.
+    pub async fn list_items_page(
+        &self,
+        filter: &Filter,
+        order: ItemOrder,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<ItemPage> {
+        let (sort_column, direction) = DB::order_column_and_direction(order);
+        let op = if direction == "ASC" { ">" } else { "<" };
+
+        let mut inner_query = String::from(
+            "
+            SELECT
+                hash, title, ext, media_kind, c.collection_id, imported_at,
+                duration, width, height, frame_rate, container,
+                video_codec, audio_codec, bitrate,
+                (
+                    SELECT COALESCE(SUM(ch.size), 0) FROM item_chunks ic
+                    JOIN chunks ch ON ch.hash = ic.chunk_hash
+                    WHERE ic.item_id = i.item_id
+                ) AS total_size
+            FROM collections c
+            JOIN items i ON c.collection_id = i.collection_id
+            WHERE 1 = 1
+            ",
+        );
+        DB::push_filter_predicates(&mut inner_query, filter);
+
+        let page_query = format!(
+            "SELECT * FROM ({inner_query}) t WHERE 1 = 1
+             AND (? OR {sort_column} {op} ? OR ({sort_column} = ? AND hash {op} ?))
+             ORDER BY {sort_column} {direction}, hash {direction}
+             LIMIT ?"
+        );
+        let mut query = sqlx::query(&page_query);
+        for tag in &filter.include_tags {
+            query = query.bind(tag);
+        }
+        for tag in &filter.exclude_tags {
+            query = query.bind(tag);
+        }
+        if let Some(substring) = &filter.title_contains {
+            query = query.bind(format!("%{substring}%"));
+        }
+        if let Some(media_kind) = filter.media_kind {
+            query = query.bind(media_kind.as_db_str());
+        }
+        if let Some(extension) = &filter.extension {
+            query = query.bind(extension);
+        }
+
+        let cursor_invalid = || Error::new(ErrorKind::InvalidCursor, "pagination-cursor-invalid");
+        let (cursor_is_none, cursor_key_numeric, cursor_key_text, cursor_hash) = match cursor {
+            None => (true, 0_i64, String::new(), String::new()),
+            Some(token) => {
+                let (key, hash) = pagination::decode_cursor(token)?;
+                match order {
+                    ItemOrder::TitleAscending | ItemOrder::TitleDescending => {
+                        (false, 0_i64, key, hash)
+                    }
+                    ItemOrder::NewestImported
+                    | ItemOrder::OldestImported
+                    | ItemOrder::SmallestFirst
+                    | ItemOrder::LargestFirst => {
+                        let numeric: i64 = key.parse().map_err(|_| cursor_invalid())?;
+                        (false, numeric, String::new(), hash)
+                    }
+                }
+            }
+        };
+        query = query.bind(cursor_is_none);
+        query = match order {
+            ItemOrder::TitleAscending | ItemOrder::TitleDescending => query
+                .bind(cursor_key_text.clone())
+                .bind(cursor_key_text.clone()),
+            ItemOrder::NewestImported
+            | ItemOrder::OldestImported
+            | ItemOrder::SmallestFirst
+            | ItemOrder::LargestFirst => query.bind(cursor_key_numeric).bind(cursor_key_numeric),
+        };
+        query = query.bind(cursor_hash).bind(page_size as i64 + 1);
+
+        let rows = query.fetch_all(&self.pool).await?;
+
+        let fetched_extra = rows.len() > page_size;
+        let mut items: Vec<Item> = Vec::with_capacity(page_size.min(rows.len()));
+        let mut sort_keys: Vec<(String, String)> = Vec::with_capacity(page_size.min(rows.len()));
+        for row in rows.iter().take(page_size) {
+            items.push(Item::from_row(row)?);
+            let hash: String = row.try_get("hash")?;
+            let key = match order {
+                ItemOrder::NewestImported | ItemOrder::OldestImported => {
+                    let imported_at: i64 = row.try_get("imported_at")?;
+                    imported_at.to_string()
+                }
+                ItemOrder::TitleAscending | ItemOrder::TitleDescending => {
+                    let title: String = row.try_get("title")?;
+                    title
+                }
+                ItemOrder::SmallestFirst | ItemOrder::LargestFirst => {
+                    let total_size: i64 = row.try_get("total_size")?;
+                    total_size.to_string()
+                }
+            };
+            sort_keys.push((key, hash));
+        }
+
+        for item in items.iter_mut() {
+            item.tags = self.tags_for_collection(item.collection_id).await?;
+        }
+
+        let next_cursor = fetched_extra
+            .then(|| sort_keys.last())
+            .flatten()
+            .map(|(key, hash)| pagination::encode_cursor(key, hash));
+
+        Ok(ItemPage::new(items, next_cursor))
+    }
+
+    /// Shared tag-hydration query used by every read method above.
+    async fn tags_for_collection(&self, collection_id: i64) -> Result<Vec<String>> {
+        let tags = sqlx::query!(
+            "
+            SELECT name FROM tags t
+            JOIN collection_tag ct
+            ON ct.tag_id = t.tag_id
+            JOIN collections c
+            ON c.collection_id = ct.collection_id
+            WHERE c.collection_id = ?
+            ",
+            collection_id
+        )
+        .map(|row| row.name)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(tags)
+    }
+}
+
+#[async_trait::async_trait]
+impl Database for DB {
+    async fn chunk_exists(&mut self, hash: &str) -> Result<bool> {
+        DB::chunk_exists(self, hash).await
+    }
+
+    async fn chunks_exist(&mut self, hashes: &[String]) -> Result<Vec<String>> {
+        DB::chunks_exist(self, hashes).await
+    }
+
+    async fn import_file_chunked(
+        &mut self,
+        title: &str,
+        hash: &str,
+        ext: &str,
+        media_kind: MediaKind,
+        tags: &[String],
+        metadata: &MediaMetadata,
+        chunks: &[(String, i64)],
+    ) -> Result<()> {
+        DB::import_file_chunked(self, title, hash, ext, media_kind, tags, metadata, chunks).await
+    }
+
+    async fn get_items(&mut self, filter: &Filter) -> Result<Vec<Item>> {
+        DB::get_items(self, filter).await
+    }
+
+    async fn list_items_page(
+        &mut self,
+        filter: &Filter,
+        order: ItemOrder,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<ItemPage> {
+        DB::list_items_page(self, filter, order, cursor, page_size).await
+    }
+
+    async fn query_items(&mut self, filter: &FilterExpr) -> Result<Vec<Item>> {
+        DB::query_items(self, filter).await
+    }
+
+    async fn query_items_page(
+        &mut self,
+        filter: &FilterExpr,
+        limit: usize,
+        offset: usize,
+    ) -> Result<QueryPage> {
+        DB::query_items_page(self, filter, limit, offset).await
+    }
+
+    async fn get_all_chunk_hashes(&mut self) -> Result<Vec<String>> {
+        DB::get_all_chunk_hashes(self).await
+    }
+
+    async fn get_all_item_hashes(&mut self) -> Result<Vec<String>> {
+        DB::get_all_item_hashes(self).await
+    }
+
+    async fn get_item_chunk_hashes(&mut self, hash: &str) -> Result<Option<Vec<String>>> {
+        DB::get_item_chunk_hashes(self, hash).await
+    }
+
+    async fn update_item_extension(&mut self, hash: &str, ext: &str) -> Result<()> {
+        DB::update_item_extension(self, hash, ext).await
+    }
+
+    async fn delete_item(&mut self, hash: &str) -> Result<Vec<String>> {
+        DB::delete_item(self, hash).await
+    }
+}
+
+/// Serializes one item as a single-line JSON object, for `DB::export_json`. Reuses `report`'s
+/// escaping helpers rather than re-implementing them.
+fn item_to_json(item: &Item) -> String {
+    let tags: Vec<String> = item
+        .tags
+        .iter()
+        .map(|tag| format!("\"{}\"", crate::report::json_escape(tag)))
+        .collect();
+    format!(
+        "{{\"title\":\"{}\",\"ext\":\"{}\",\"hash\":\"{}\",\"media_kind\":\"{}\",\"tags\":[{}]}}",
+        crate::report::json_escape(&item.title),
+        crate::report::json_escape(&item.ext),
+        crate::report::json_escape(&item.hash),
+        item.media_kind.as_db_str(),
+        tags.join(",")
+    )
+}
+
+/// One item parsed from an `import_json` array entry.
+struct JsonItem {
+    title: String,
+    ext: String,
+    hash: String,
+    media_kind: String,
+    tags: Vec<String>,
+}
+
+/// Rejects any `hash` that isn't exactly `ITEM_HASH_HEX_LEN` lowercase hex digits, the same check
+/// `archive::validate_member_hash` applies to an archive manifest's member hashes. `hash` here
+/// comes from a JSON catalog snapshot `import_json` never otherwise validates the shape of, and it
+/// flows unchecked into `thumbnail::path_for`'s `hash[0..2]`/`hash[2..]` slicing from
+/// `Repo::check_data_integrity`, `Repo::delete_item`, and `Repo::detect_extension_mismatch` once
+/// inserted — a hash shorter than 2 bytes panics that slice, and one containing `/`/`..` is a
+/// path-traversal primitive into an attacker-chosen file.
+fn validate_item_hash(hash: &str) -> Result<()> {
+    if utils::is_lowercase_hex(hash, ITEM_HASH_HEX_LEN) {
+        Ok(())
+    } else {
+        Err(json_invalid())
+    }
+}
+
+fn json_invalid() -> Error {
+    Error::new(ErrorKind::InvalidJson, "json-invalid")
+}
+
+/// A minimal hand-rolled JSON reader, just enough for `DB::import_json` to parse the array
+/// `export_json` produces while tolerating object fields it doesn't recognize. Not a general JSON
+/// library: `skip_value` handles any JSON value so unrecognized fields never trip up parsing, but
+/// `string` only decodes the escapes `report::json_escape` can actually produce (`\\`, `\"`, `\n`,
+/// `\r`, `\t`, and `\u00XX` for other control characters).
+struct JsonCursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> JsonCursor<'a> {
+    fn new(input: &'a str) -> Self {
+        JsonCursor {
+            bytes: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(json_invalid())
+        }
+    }
+
+    /// Parses a `"..."` JSON string, decoding the escapes `report::json_escape` can produce:
+    /// `\\`, `\"`, `\n`, `\r`, `\t`, and `\uXXXX`.
+    fn string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+        loop {
+            match self.peek().ok_or_else(json_invalid)? {
+                b'"' => {
+                    self.pos += 1;
+                    return Ok(value);
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    let escaped = self.peek().ok_or_else(json_invalid)?;
+                    self.pos += 1;
+                    match escaped {
+                        b'\\' => value.push('\\'),
+                        b'"' => value.push('"'),
+                        b'n' => value.push('\n'),
+                        b'r' => value.push('\r'),
+                        b't' => value.push('\t'),
+                        b'u' => value.push(self.unicode_escape()?),
+                        _ => return Err(json_invalid()),
+                    }
+                }
+                _ => {
+                    // Strings parsed here are always ASCII (hashes, extensions, tag names), but
+                    // fall back to reading one UTF-8 codepoint rather than one byte so a title
+                    // containing non-ASCII text still round-trips.
+                    let remaining = std::str::from_utf8(&self.bytes[self.pos..]).unwrap_or("");
+                    let ch = remaining.chars().next().ok_or_else(json_invalid)?;
+                    value.push(ch);
+                    self.pos += ch.len_utf8();
+                }
+            }
+        }
+    }
+
+    /// Parses the 4 hex digits of a `\uXXXX` escape (the `\u` itself already consumed) into the
+    /// codepoint they encode. `report::json_escape` only ever emits this for a control character
+    /// below `0x20`, so the result always fits in a single `char` on its own, with no surrogate
+    /// pairs to reassemble.
+    fn unicode_escape(&mut self) -> Result<char> {
+        let digits = self.bytes.get(self.pos..self.pos + 4).ok_or_else(json_invalid)?;
+        let digits = std::str::from_utf8(digits).map_err(|_| json_invalid())?;
+        let codepoint = u32::from_str_radix(digits, 16).map_err(|_| json_invalid())?;
+        let ch = char::from_u32(codepoint).ok_or_else(json_invalid)?;
+        self.pos += 4;
+        Ok(ch)
+    }
+
+    /// Parses a `["a","b"]` JSON array of strings.
+    fn string_array(&mut self) -> Result<Vec<String>> {
+        self.expect(b'[')?;
+        self.skip_ws();
+        let mut values = Vec::new();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(values);
+        }
+        loop {
+            self.skip_ws();
+            values.push(self.string()?);
+            self.skip_ws();
+            match self.peek().ok_or_else(json_invalid)? {
+                b',' => self.pos += 1,
+                b']' => {
+                    self.pos += 1;
+                    return Ok(values);
+                }
+                _ => return Err(json_invalid()),
+            }
+        }
+    }
+
+    /// Skips over one JSON value of any kind, without interpreting it, so `object` can tolerate
+    /// fields it doesn't recognize.
+    fn skip_value(&mut self) -> Result<()> {
+        self.skip_ws();
+        match self.peek().ok_or_else(json_invalid)? {
+            b'"' => {
+                self.string()?;
+            }
+            b'[' => {
+                self.pos += 1;
+                self.skip_ws();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        self.skip_value()?;
+                        self.skip_ws();
+                        match self.peek().ok_or_else(json_invalid)? {
+                            b',' => {
+                                self.pos += 1;
+                                self.skip_ws();
+                            }
+                            b']' => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return Err(json_invalid()),
+                        }
+                    }
+                }
+            }
+            b'{' => {
+                self.pos += 1;
+                self.skip_ws();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                } else {
+                    loop {
+                        self.skip_ws();
+                        self.string()?;
+                        self.skip_ws();
+                        self.expect(b':')?;
+                        self.skip_value()?;
+                        self.skip_ws();
+                        match self.peek().ok_or_else(json_invalid)? {
+                            b',' => {
+                                self.pos += 1;
+                                self.skip_ws();
+                            }
+                            b'}' => {
+                                self.pos += 1;
+                                break;
+                            }
+                            _ => return Err(json_invalid()),
+                        }
+                    }
+                }
+            }
+            _ => {
+                // Number, `true`, `false`, or `null`: none of these appear in what `export_json`
+                // emits, but skip up to the next structural character so a future export format
+                // adding one doesn't break `import_json` against an older build.
+                while matches!(self.peek(), Some(byte) if !matches!(byte, b',' | b']' | b'}')) {
+                    self.pos += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses one item object, collecting the fields `import_json` understands and skipping any
+    /// other.
+    fn item_object(&mut self) -> Result<JsonItem> {
+        self.expect(b'{')?;
+        self.skip_ws();
+        let mut title = None;
+        let mut ext = None;
+        let mut hash = None;
+        let mut media_kind = None;
+        let mut tags = Vec::new();
+        if self.peek() != Some(b'}') {
+            loop {
+                self.skip_ws();
+                let key = self.string()?;
+                self.skip_ws();
+                self.expect(b':')?;
+                self.skip_ws();
+                match key.as_str() {
+                    "title" => title = Some(self.string()?),
+                    "ext" => ext = Some(self.string()?),
+                    "hash" => hash = Some(self.string()?),
+                    "media_kind" => media_kind = Some(self.string()?),
+                    "tags" => tags = self.string_array()?,
+                    _ => self.skip_value()?,
+                }
+                self.skip_ws();
+                match self.peek().ok_or_else(json_invalid)? {
+                    b',' => {
+                        self.pos += 1;
+                        self.skip_ws();
+                    }
+                    b'}' => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(json_invalid()),
+                }
+            }
+        } else {
+            self.pos += 1;
+        }
+        let hash = hash.ok_or_else(json_invalid)?;
+        validate_item_hash(&hash)?;
+        Ok(JsonItem {
+            title: title.ok_or_else(json_invalid)?,
+            ext: ext.ok_or_else(json_invalid)?,
+            hash,
+            media_kind: media_kind.ok_or_else(json_invalid)?,
+            tags,
+        })
+    }
+}
+
+/// Parses the top-level JSON array `export_json` produces.
+fn parse_json_items(input: &str) -> Result<Vec<JsonItem>> {
+    let mut cursor = JsonCursor::new(input);
+    cursor.skip_ws();
+    cursor.expect(b'[')?;
+    cursor.skip_ws();
+    let mut items = Vec::new();
+    if cursor.peek() == Some(b']') {
+        return Ok(items);
+    }
+    loop {
+        cursor.skip_ws();
+        items.push(cursor.item_object()?);
+        cursor.skip_ws();
+        match cursor.peek().ok_or_else(json_invalid)? {
+            b',' => {
+                cursor.pos += 1;
+            }
+            b']' => break,
+            _ => return Err(json_invalid()),
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_context::{test_context, AsyncTestContext};
+    use tokio::time::{sleep, Duration};
+    use uuid::Uuid;
+
+    /// A valid-shaped item hash for tests that exercise `import_json`'s `validate_item_hash`
+    /// check: `fill` repeated out to `ITEM_HASH_HEX_LEN`, so distinct fill characters give
+    /// distinct hashes.
+    fn item_hash(fill: char) -> String {
+        fill.to_string().repeat(ITEM_HASH_HEX_LEN)
+    }
+
+    struct TempFolder {
+        pub path: std::path::PathBuf,
+    }
+
+    #[async_trait::async_trait]
+    impl AsyncTestContext for TempFolder {
+        async fn setup() -> TempFolder {
+            let uuid = Uuid::new_v4();
+            let temp_dir_path =
+                String::from("temp-") + uuid.hyphenated().encode_lower(&mut Uuid::encode_buffer());
+            let temp_dir = std::path::PathBuf::from(temp_dir_path);
+            fs::create_dir(&temp_dir).expect("Failed to create temp dir for testing.");
+            TempFolder { path: temp_dir }
+        }
+
+        async fn teardown(self) {
+            if let Err(_) = fs::remove_dir_all(&self.path) {
+                // If the first try failed, wait a bit and retry
+                sleep(Duration::from_millis(200)).await;
+                fs::remove_dir_all(&self.path).expect("Failed to teardown temp test directory.")
+            };
+        }
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_create_db_success(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+
+        // WHEN
+        DB::new(&db_path).await?;
+
+        // THEN
+        // Verify a connection can be opened
+        let mut db = SqliteConnection::connect(&db_path.to_string_lossy()).await?;
+
+        // Verify required tables
+        let num_rows = sqlx::query!(
+            "
+            SELECT tbl_name FROM sqlite_master
+            WHERE type='table'
+            AND tbl_name IN (
+                'tags', 'items', 'collections', 'collection_tag', 'title_fts'
+            );
+            ",
+        )
+        .fetch_all(&mut db)
+        .await?
+        .len();
+        assert_eq!(num_rows, 5);
+
+        // Verify required indices
+        let num_rows = sqlx::query!(
+            "
+            SELECT tbl_name FROM sqlite_master
+            WHERE type='index'
+            AND name IN (
+                'hash_index', 'tag_index'
+            );
+            ",
+        )
+        .fetch_all(&mut db)
+        .await?
+        .len();
+        assert_eq!(num_rows, 2);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_create_db_failed_db(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+
+        // Create a folder at the target db
+        fs::create_dir_all(&db_path)?;
+
+        // WHEN
+        let result = DB::new(&db_path).await;
+
+        // THEN
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(matches!(error.kind, ErrorKind::DB));
+        }
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_create_db_failed_io(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("parent").join("vorg.db");
+
+        // Create a file at the parent path
+        let parent_path = db_path
+            .parent()
+            .ok_or(Error::new(ErrorKind::IO, "db-path-parent"))?;
+        fs::File::create(parent_path)?;
+
+        // WHEN
+        let result = DB::new(&db_path).await;
+
+        // THEN
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert!(matches!(error.kind, ErrorKind::IO));
+        }
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_open_db_success(ctx: &TempFolder) -> Result<()> {
+        // GIVEN an existing, already-migrated db
+        let db_path = ctx.path.join("vorg.db");
+        DB::new(&db_path).await?;
+
+        // WHEN opened again
+        // THEN it connects without re-running any migration
+        DB::new(&db_path).await?;
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_migrate_adopts_pre_migration_db_without_rerunning_schema(
+        ctx: &TempFolder,
+    ) -> Result<()> {
+        // GIVEN a db created the way `create_db` used to, before migrations existed: the schema
+        // is there, but nothing ever stamped `user_version`, so it reads as 0.
+        let db_path = ctx.path.join("vorg.db");
+        Sqlite::create_database(&db_path.to_string_lossy()).await?;
+        let mut connection = SqliteConnection::connect(&db_path.to_string_lossy()).await?;
+        sqlx::query(DB::MIGRATIONS[0])
+            .execute(&mut connection)
+            .await?;
+        connection.close().await?;
+
+        // WHEN opened through DB::new
+        let result = DB::new(&db_path).await;
+
+        // THEN it succeeds, rather than failing trying to CREATE TABLEs that already exist
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_migrate_rejects_database_from_a_newer_binary(ctx: &TempFolder) -> Result<()> {
+        // GIVEN a db whose user_version is past what this binary's migrations know about
+        let db_path = ctx.path.join("vorg.db");
+        DB::new(&db_path).await?;
+        let mut connection = SqliteConnection::connect(&db_path.to_string_lossy()).await?;
+        sqlx::query(&format!(
+            "PRAGMA user_version = {}",
+            DB::MIGRATIONS.len() + 1
+        ))
+        .execute(&mut connection)
+        .await?;
+        connection.close().await?;
+
+        // WHEN reopened
+        let result = DB::new(&db_path).await;
+
+        // THEN it fails cleanly instead of silently proceeding against an unknown schema
+        assert!(result.is_err());
+        if let Err(error) = result {
+            assert_eq!(error.kind, ErrorKind::DB);
+            assert_eq!(
+                error.to_string(),
+                "This database was created by a newer version of vorg; upgrade vorg to open it."
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_import_file(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+
+        // WHEN
+        // Import file
+        let title = "Test title";
+        let ext = "mp4";
+        let hash = "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79";
+        let result = db
+            .import_file(&title, &hash, &ext, MediaKind::Video)
+            .await;
+
+        // THEN
+        assert!(result.is_ok());
+        // Test file has been imported
+        let mut connection = SqliteConnection::connect(&db_path.to_string_lossy()).await?;
+        let item_exists_query = "
+        SELECT hash FROM collections c, items i, collection_tag ct, tags t
+        WHERE c.collection_id=ct.collection_id
+        AND ct.tag_id=t.tag_id
+        AND i.collection_id=c.collection_id
+        AND t.name='meta:Incomplete'
+        AND title=?
+        AND ext=?
+        AND hash=?
+        ";
+        assert_eq!(
+            sqlx::query(item_exists_query)
+                .bind(title)
+                .bind(ext)
+                .bind(hash)
+                .fetch_all(&mut connection)
+                .await?
+                .len(),
+            1
+        );
+
+        // WHEN
+        // Test duplicate import
+        let duplicate_title = "Another title";
+        let result = db
+            .import_file(duplicate_title, &hash, &ext, MediaKind::Video)
+            .await;
+
+        // THEN
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "The item to import already exists in the database."
+        );
+        // Make sure no redundant collection is created.
+        assert_eq!(
+            sqlx::query!(
+                "
+                SELECT title FROM collections
+                WHERE title = ?
+                ",
+                duplicate_title
+            )
+            .fetch_all(&mut connection)
+            .await?
+            .len(),
+            0
+        );
+
+        // WHEN
+        // Test reusing tag
+        let hash2 = "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633";
+        let title2 = "Some title";
+        let result = db
+            .import_file(&title2, &hash2, &ext, MediaKind::Video)
+            .await;
+
+        // THEN
+        assert!(result.is_ok());
+        assert_eq!(
+            sqlx::query(item_exists_query)
+                .bind(title2)
+                .bind(ext)
+                .bind(hash2)
+                .fetch_all(&mut connection)
+                .await?
+                .len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_get_items(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+
+        // Import file
+        let title = "Test title";
+        let ext = "mp4";
+        let hash = "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79";
+        let result = db
+            .import_file(&title, &hash, &ext, MediaKind::Video)
+            .await;
+        assert!(result.is_ok());
+
+        // WHEN
+        let items = db.get_items(&Filter::new()).await?;
+
+        // THEN
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, title);
+        assert_eq!(items[0].ext, ext);
+        assert_eq!(items[0].hash, hash);
+        assert_eq!(items[0].media_kind, MediaKind::Video);
+        assert_eq!(items[0].tags.len(), 1);
+        assert_eq!(items[0].tags[0], "meta:Incomplete");
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_get_items_filters_by_tag(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        let hash = "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79";
+        db.import_file("Test title", &hash, "mp4", MediaKind::Video)
+            .await?;
+
+        // WHEN/THEN
+        // Every fresh import is tagged meta:Incomplete, so requiring it keeps the item...
+        let items = db.get_items(&Filter::with_tag("meta:Incomplete")).await?;
+        assert_eq!(items.len(), 1);
+        // ...and excluding it drops the item.
+        let items = db
+            .get_items(&Filter::new().exclude_tag("meta:Incomplete"))
+            .await?;
+        assert_eq!(items.len(), 0);
+        // A tag nothing carries matches nothing.
+        let items = db.get_items(&Filter::with_tag("meta:Missing")).await?;
+        assert_eq!(items.len(), 0);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_get_items_filters_by_title_and_media_kind(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Vacation video",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Vacation photo",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "png",
+            MediaKind::Image,
+        )
+        .await?;
+
+        // WHEN/THEN
+        let items = db.get_items(&Filter::new().title_contains("Vacation")).await?;
+        assert_eq!(items.len(), 2);
+
+        let items = db
+            .get_items(&Filter::new().title_contains("video"))
+            .await?;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Vacation video");
+
+        let items = db
+            .get_items(&Filter::new().of_kind(MediaKind::Image))
+            .await?;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Vacation photo");
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_get_items_filters_by_extension(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Vacation video",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Vacation photo",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "png",
+            MediaKind::Image,
+        )
+        .await?;
+
+        // WHEN
+        let items = db.get_items(&Filter::new().of_extension("png")).await?;
+
+        // THEN
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Vacation photo");
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_search_collections_ranks_matches(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Summer vacation",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Birthday party",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN
+        let items = db.search_collections("vacation", false).await?;
+
+        // THEN
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Summer vacation");
+        assert_eq!(items[0].tags.len(), 1);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_search_collections_treats_fts5_syntax_as_literal_by_default(
+        ctx: &TempFolder,
+    ) -> Result<()> {
+        // GIVEN a title that happens to look like FTS5 query syntax
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "foo \"bar\" AND baz",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN searching for that exact text
+        let items = db.search_collections("foo \"bar\" AND baz", false).await?;
+
+        // THEN it matches literally rather than throwing a syntax error
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "foo \"bar\" AND baz");
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_search_collections_raw_fts_syntax_supports_operators(
+        ctx: &TempFolder,
+    ) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Summer vacation",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Birthday party",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN a power-user query uses the `OR` operator, which plain sanitizing would have
+        // treated as a literal term instead
+        let items = db.search_collections("vacation OR birthday", true).await?;
+
+        // THEN both match
+        assert_eq!(items.len(), 2);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_query_items_empty_filter_returns_everything(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Summer vacation",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Birthday party",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN
+        let items = db.query_items(&crate::query::parse("").unwrap()).await?;
+
+        // THEN
+        assert_eq!(items.len(), 2);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_query_items_filters_by_tag(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        let hash = "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79";
+        db.import_file("Test title", hash, "mp4", MediaKind::Video)
+            .await?;
+
+        // WHEN/THEN
+        let items = db
+            .query_items(&crate::query::parse("tag:meta:Incomplete").unwrap())
+            .await?;
+        assert_eq!(items.len(), 1);
+
+        let items = db
+            .query_items(&crate::query::parse("tag:meta:Missing").unwrap())
+            .await?;
+        assert_eq!(items.len(), 0);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_query_items_or_combines_matches(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Summer vacation",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Birthday party",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN
+        let filter = crate::query::parse("title:\"Summer vacation\" OR title:\"Birthday party\"")
+            .unwrap();
+        let items = db.query_items(&filter).await?;
+
+        // THEN
+        assert_eq!(items.len(), 2);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_query_items_not_excludes_matches(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Summer vacation",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Birthday party",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN
+        let filter = crate::query::parse("NOT title:\"Summer vacation\"").unwrap();
+        let items = db.query_items(&filter).await?;
+
+        // THEN
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Birthday party");
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_query_items_and_with_parens(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Summer vacation",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Birthday party",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.add_tag_to_collection(1, "favorite").await?;
+
+        // WHEN
+        let query = "tag:favorite AND (title:\"Summer vacation\" OR title:\"Birthday party\")";
+        let filter = crate::query::parse(query).unwrap();
+        let items = db.query_items(&filter).await?;
+
+        // THEN
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Summer vacation");
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_query_items_tag_prefix_matches_every_tag_in_the_namespace(
+        ctx: &TempFolder,
+    ) -> Result<()> {
+        // GIVEN one item left at the default "meta:Incomplete" tag and one tagged "artist:foo"
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Summer vacation",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Birthday party",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.add_tag_to_collection(2, "artist:foo").await?;
+
+        // WHEN querying the "meta:*" namespace
+        let items = db
+            .query_items(&crate::query::parse("tag:meta:*").unwrap())
+            .await?;
+
+        // THEN only the item still carrying a "meta:" tag matches
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Summer vacation");
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_query_items_page_paginates_and_reports_total_count(
+        ctx: &TempFolder,
+    ) -> Result<()> {
+        // GIVEN three matching items
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Alpha",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Bravo",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Charlie",
+            "1f2d8f0e4fd7b8e1c28cf0e5b07c7d9ebaf64b6e77bcf9ab0d1234567",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        let filter = crate::query::parse("").unwrap();
+
+        // WHEN fetching the first page of 2
+        let page = db.query_items_page(&filter, 2, 0).await?;
+
+        // THEN it reports the full total but only returns the page's items
+        assert_eq!(page.total_count, 3);
+        assert_eq!(page.items.len(), 2);
+
+        // WHEN fetching the next page
+        let next_page = db.query_items_page(&filter, 2, 2).await?;
+
+        // THEN the remaining item is returned
+        assert_eq!(next_page.total_count, 3);
+        assert_eq!(next_page.items.len(), 1);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_open_read_only_reads_items(ctx: &TempFolder) -> Result<()> {
+        // GIVEN a db created and populated through a regular read-write handle
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Summer vacation",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN opened read-only
+        let read_db = DB::open_read_only(&db_path).await?;
+
+        // THEN its read queries see the same data
+        let items = read_db.get_items(&Filter::new()).await?;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Summer vacation");
+
+        let items = read_db.search_collections("vacation", false).await?;
+        assert_eq!(items.len(), 1);
+
+        let items = read_db
+            .query_items(&crate::query::parse("tag:meta:Incomplete").unwrap())
+            .await?;
+        assert_eq!(items.len(), 1);
+
+        let page = read_db
+            .list_items_page(&Filter::new(), ItemOrder::TitleAscending, None, 10)
+            .await?;
+        assert_eq!(page.items.len(), 1);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_open_read_only_rejects_missing_db(ctx: &TempFolder) -> Result<()> {
+        // GIVEN a path with no db file
+        let db_path = ctx.path.join("vorg.db");
+
+        // WHEN/THEN opening read-only fails rather than creating one, unlike `DB::new`
+        let result = DB::open_read_only(&db_path).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_list_items_page_orders_by_title(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Banana",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Apple",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN
+        let ascending = db
+            .list_items_page(&Filter::new(), ItemOrder::TitleAscending, None, 10)
+            .await?;
+        let descending = db
+            .list_items_page(&Filter::new(), ItemOrder::TitleDescending, None, 10)
+            .await?;
+
+        // THEN
+        assert_eq!(ascending.items.len(), 2);
+        assert_eq!(ascending.items[0].title, "Apple");
+        assert_eq!(ascending.items[1].title, "Banana");
+        assert!(ascending.is_exhausted());
+        assert_eq!(descending.items[0].title, "Banana");
+        assert_eq!(descending.items[1].title, "Apple");
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_list_items_page_orders_by_import_time(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "First",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        // imported_at has one-second resolution; sleep past it so the two imports sort distinctly.
+        sleep(Duration::from_secs(1)).await;
+        db.import_file(
+            "Second",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
+        // WHEN
+        let newest_first = db
+            .list_items_page(&Filter::new(), ItemOrder::NewestImported, None, 10)
+            .await?;
+        let oldest_first = db
+            .list_items_page(&Filter::new(), ItemOrder::OldestImported, None, 10)
+            .await?;
+
+        // THEN
+        assert_eq!(newest_first.items[0].title, "Second");
+        assert_eq!(newest_first.items[1].title, "First");
+        assert_eq!(oldest_first.items[0].title, "First");
+        assert_eq!(oldest_first.items[1].title, "Second");
+
+        Ok(())
+    }
+
     #[test_context(TempFolder)]
     #[tokio::test]
-    async fn test_create_db_success(ctx: &TempFolder) -> Result<()> {
+    async fn test_list_items_page_paginates_with_cursor(ctx: &TempFolder) -> Result<()> {
         // GIVEN
         let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "Apple",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Banana",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        db.import_file(
+            "Cherry",
+            "7a8f0f1ea9f8860a209b837a70a704bcb4a1ef3957c8a6b4a3a0b6b6",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
 
         // WHEN
-        DB::new(&db_path).await?;
+        let first_page = db
+            .list_items_page(&Filter::new(), ItemOrder::TitleAscending, None, 2)
+            .await?;
+        assert!(!first_page.is_exhausted());
+        let second_page = db
+            .list_items_page(
+                &Filter::new(),
+                ItemOrder::TitleAscending,
+                first_page.next_cursor(),
+                2,
+            )
+            .await?;
 
         // THEN
-        // Verify a connection can be opened
-        let mut db = SqliteConnection::connect(&db_path.to_string_lossy()).await?;
-
-        // Verify required tables
-        let num_rows = sqlx::query!(
-            "
-            SELECT tbl_name FROM sqlite_master
-            WHERE type='table'
-            AND tbl_name IN (
-                'tags', 'items', 'collections', 'collection_tag', 'title_fts'
-            );
-            ",
-        )
-        .fetch_all(&mut db)
-        .await?
-        .len();
-        assert_eq!(num_rows, 5);
-
-        // Verify required indices
-        let num_rows = sqlx::query!(
-            "
-            SELECT tbl_name FROM sqlite_master
-            WHERE type='index'
-            AND name IN (
-                'hash_index', 'tag_index'
-            );
-            ",
-        )
-        .fetch_all(&mut db)
-        .await?
-        .len();
-        assert_eq!(num_rows, 2);
+        assert_eq!(first_page.items.len(), 2);
+        assert_eq!(first_page.items[0].title, "Apple");
+        assert_eq!(first_page.items[1].title, "Banana");
+        assert!(second_page.is_exhausted());
+        assert_eq!(second_page.items.len(), 1);
+        assert_eq!(second_page.items[0].title, "Cherry");
 
         Ok(())
     }
 
     #[test_context(TempFolder)]
     #[tokio::test]
-    async fn test_create_db_failed_db(ctx: &TempFolder) -> Result<()> {
+    async fn test_list_items_page_rejects_malformed_cursor(ctx: &TempFolder) -> Result<()> {
         // GIVEN
         let db_path = ctx.path.join("vorg.db");
-
-        // Create a folder at the target db
-        fs::create_dir_all(&db_path)?;
+        let mut db = DB::new(&db_path).await.unwrap();
 
         // WHEN
-        let result = DB::new(&db_path).await;
+        let result = db
+            .list_items_page(
+                &Filter::new(),
+                ItemOrder::TitleAscending,
+                Some("not a cursor"),
+                2,
+            )
+            .await;
 
         // THEN
         assert!(result.is_err());
-        if let Err(error) = result {
-            assert!(matches!(error.kind, ErrorKind::DB));
-        }
 
         Ok(())
     }
 
     #[test_context(TempFolder)]
     #[tokio::test]
-    async fn test_create_db_failed_io(ctx: &TempFolder) -> Result<()> {
+    async fn test_latest_seq_is_zero_on_empty_db(ctx: &TempFolder) -> Result<()> {
         // GIVEN
-        let db_path = ctx.path.join("parent").join("vorg.db");
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
 
-        // Create a file at the parent path
-        let parent_path = db_path.parent().ok_or(Error {
-            kind: ErrorKind::IO,
-            msg: String::from("Failed to get db path parent."),
-        })?;
-        fs::File::create(parent_path)?;
+        // WHEN/THEN
+        assert_eq!(db.latest_seq().await?, 0);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_import_file_records_an_add_item_change(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
 
         // WHEN
-        let result = DB::new(&db_path).await;
+        db.import_file(
+            "Test title",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
 
-        // THEN
-        assert!(result.is_err());
-        if let Err(error) = result {
-            assert!(matches!(error.kind, ErrorKind::IO));
-        }
+        // THEN import_file itself tags the item meta:Incomplete, so it records two changes:
+        // the item add and the tag add.
+        assert_eq!(db.latest_seq().await?, 2);
+        let changes = db.changes_since(0).await?;
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].seq, 1);
+        assert_eq!(changes[0].parent_seq, None);
+        assert_eq!(changes[0].operation, ChangeOperation::AddItem);
+        assert_eq!(changes[0].collection_id, Some(1));
+        assert_eq!(changes[1].seq, 2);
+        assert_eq!(changes[1].parent_seq, Some(1));
+        assert_eq!(changes[1].operation, ChangeOperation::AddTag);
+        assert_eq!(changes[1].tag.as_deref(), Some("meta:Incomplete"));
 
         Ok(())
     }
 
+    #[test_context(TempFolder)]
     #[tokio::test]
-    async fn test_open_db_success() -> Result<()> {
-        DB::new("resources/db/valid.db").await?;
+    async fn test_delete_item_records_a_remove_item_change(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        let hash = "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79";
+        db.import_file("Test title", hash, "mp4", MediaKind::Video)
+            .await?;
+        let seq_before_delete = db.latest_seq().await?;
+
+        // WHEN
+        db.delete_item(hash).await?;
+
+        // THEN
+        let changes = db.changes_since(seq_before_delete).await?;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].operation, ChangeOperation::RemoveItem);
+        assert_eq!(changes[0].parent_seq, Some(seq_before_delete));
 
         Ok(())
     }
 
-    #[rstest]
-    #[case(
-        "resources/db/invalid_unexpected_table.db",
-        "Unexpected table \"table_unexpected\" exists in the database."
-    )]
-    #[case(
-        "resources/db/invalid_missing_table.db",
-        "Table \"items\" is missing from the database."
-    )]
-    #[case(
-        "resources/db/invalid_unexpected_column.db",
-        "Unexpected column \"studio_id\" in table \"items\"."
-    )]
-    #[case(
-        "resources/db/invalid_missing_column.db",
-        "Column \"ext\" is missing from table \"items\"."
-    )]
-    #[case(
-        "resources/db/invalid_wrong_column_type.db",
-        "Column \"hash\" in table \"items\" should have type \"VARCHAR(64)\"."
-    )]
-    #[case(
-        "resources/db/invalid_missing_index.db",
-        "Database has unexpected or missing indices."
-    )]
-    #[case(
-        "resources/db/invalid_missing_trigger.db",
-        "Database has unexpected or missing triggers."
-    )]
+    #[test_context(TempFolder)]
     #[tokio::test]
-    async fn test_open_db_error(#[case] db_path: &str, #[case] err_msg: &str) {
+    async fn test_changes_since_only_returns_newer_changes(ctx: &TempFolder) -> Result<()> {
+        // GIVEN
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file(
+            "First",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+        let seq_after_first = db.latest_seq().await?;
+        db.import_file(
+            "Second",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+        )
+        .await?;
+
         // WHEN
-        let result = DB::new(db_path).await;
+        let changes = db.changes_since(seq_after_first).await?;
 
-        // THEN
-        assert!(result.is_err());
-        if let Err(error) = result {
-            assert_eq!(error.kind, ErrorKind::DB);
-            assert_eq!(error.to_string(), err_msg);
-        }
+        // THEN only the second import's changes are returned, oldest first
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|change| change.seq > seq_after_first));
+        assert!(changes[0].seq < changes[1].seq);
+
+        Ok(())
     }
 
     #[test_context(TempFolder)]
     #[tokio::test]
-    async fn test_import_file(ctx: &TempFolder) -> Result<()> {
+    async fn test_host_id_is_stable_across_reopening_the_same_db(ctx: &TempFolder) -> Result<()> {
         // GIVEN
         let db_path = ctx.path.join("vorg.db");
         let mut db = DB::new(&db_path).await.unwrap();
+        let first_host_id = db.host_id().await?;
 
-        // WHEN
-        // Import file
-        let title = "Test title";
-        let ext = "mp4";
-        let hash = "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79";
-        let result = db.import_file(&title, &hash, &ext).await;
+        // WHEN the db is reopened
+        let mut db = DB::new(&db_path).await.unwrap();
 
-        // THEN
-        assert!(result.is_ok());
-        // Test file has been imported
-        let mut connection = SqliteConnection::connect(&db_path.to_string_lossy()).await?;
-        let item_exists_query = "
-        SELECT hash FROM collections c, items i, collection_tag ct, tags t
-        WHERE c.collection_id=ct.collection_id
-        AND ct.tag_id=t.tag_id
-        AND i.collection_id=c.collection_id
-        AND t.name='meta:Incomplete'
-        AND title=?
-        AND ext=?
-        AND hash=?
-        ";
-        assert_eq!(
-            sqlx::query(item_exists_query)
-                .bind(title)
-                .bind(ext)
-                .bind(hash)
-                .fetch_all(&mut connection)
-                .await?
-                .len(),
-            1
-        );
+        // THEN it reports the same host id rather than generating a new one
+        assert_eq!(db.host_id().await?, first_host_id);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_import_file_chunked_records_a_change_per_tag(ctx: &TempFolder) -> Result<()> {
+        // GIVEN a file imported with two tags, each added by `Tx::add_tag_to_collection` inside
+        // `import_file_chunked`'s one transaction.
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        let tags = vec!["Animal".to_string(), "Cat".to_string()];
 
         // WHEN
-        // Test duplicate import
-        let duplicate_title = "Another title";
-        let result = db.import_file(duplicate_title, &hash, &ext).await;
+        db.import_file_chunked(
+            "Test title",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+            &tags,
+            &MediaMetadata::default(),
+            &[("chunk-a".to_string(), 1024)],
+        )
+        .await?;
 
-        // THEN
-        assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err().to_string(),
-            "The item to import already exists in the database."
-        );
-        // Make sure no redundant collection is created.
-        assert_eq!(
-            sqlx::query!(
-                "
-                SELECT title FROM collections
-                WHERE title = ?
-                ",
-                duplicate_title
+        // THEN one AddItem change and one AddTag change per tag are recorded, committed together
+        // with the item in that transaction.
+        let changes = db.changes_since(0).await?;
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].operation, ChangeOperation::AddItem);
+        assert_eq!(changes[1].operation, ChangeOperation::AddTag);
+        assert_eq!(changes[1].tag.as_deref(), Some("Animal"));
+        assert_eq!(changes[2].operation, ChangeOperation::AddTag);
+        assert_eq!(changes[2].tag.as_deref(), Some("Cat"));
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_delete_item_reclaims_chunks_whose_refcount_drops_to_zero(
+        ctx: &TempFolder,
+    ) -> Result<()> {
+        // GIVEN two items sharing one chunk, each with one chunk of their own
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        db.import_file_chunked(
+            "First",
+            "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79",
+            "mp4",
+            MediaKind::Video,
+            &[],
+            &MediaMetadata::default(),
+            &[("shared".to_string(), 1024), ("only-first".to_string(), 512)],
+        )
+        .await?;
+        db.import_file_chunked(
+            "Second",
+            "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
+            "mp4",
+            MediaKind::Video,
+            &[],
+            &MediaMetadata::default(),
+            &[("shared".to_string(), 1024)],
+        )
+        .await?;
+
+        // WHEN the first item is deleted
+        let reclaimed = db
+            .delete_item("09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79")
+            .await?;
+
+        // THEN only the chunk unique to the first item is reclaimed; the shared chunk survives,
+        // still referenced by the second item
+        assert_eq!(reclaimed, vec!["only-first".to_string()]);
+        let remaining_hashes = db.get_all_chunk_hashes().await?;
+        assert_eq!(remaining_hashes, vec!["shared".to_string()]);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_export_json_then_import_json_into_fresh_db_round_trips(
+        ctx: &TempFolder,
+    ) -> Result<()> {
+        // GIVEN a db with two items, one of them carrying custom tags and a title with control
+        // characters `json_escape` must escape and `JsonCursor::string` must decode back exactly
+        let source_path = ctx.path.join("source.db");
+        let mut source = DB::new(&source_path).await.unwrap();
+        source
+            .import_file("First\twith a\nnewline", &item_hash('1'), "mp4", MediaKind::Video)
+            .await?;
+        source
+            .import_file_chunked(
+                "Second",
+                &item_hash('2'),
+                "png",
+                MediaKind::Image,
+                &[String::from("favorite"), String::from("2024")],
+                &MediaMetadata::default(),
+                &[],
             )
-            .fetch_all(&mut connection)
-            .await?
-            .len(),
-            0
-        );
+            .await?;
 
-        // WHEN
-        // Test reusing tag
-        let hash2 = "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633";
-        let title2 = "Some title";
-        let result = db.import_file(&title2, &hash2, &ext).await;
+        // WHEN its catalog is exported to JSON and replayed into a fresh db
+        let mut exported = Vec::new();
+        source.export_json(&mut exported).await?;
+        let destination_path = ctx.path.join("destination.db");
+        let mut destination = DB::new(&destination_path).await.unwrap();
+        destination.import_json(exported.as_slice()).await?;
+
+        // THEN the fresh db's items match the source on every field the JSON format preserves
+        let mut source_items = source.get_items(&Filter::new()).await?;
+        let mut destination_items = destination.get_items(&Filter::new()).await?;
+        source_items.sort_by(|a, b| a.hash.cmp(&b.hash));
+        destination_items.sort_by(|a, b| a.hash.cmp(&b.hash));
+        assert_eq!(source_items.len(), destination_items.len());
+        for (source_item, destination_item) in source_items.iter().zip(destination_items.iter()) {
+            assert_eq!(source_item.hash, destination_item.hash);
+            assert_eq!(source_item.title, destination_item.title);
+            assert_eq!(source_item.ext, destination_item.ext);
+            assert_eq!(source_item.media_kind, destination_item.media_kind);
+            assert_eq!(source_item.tags, destination_item.tags);
+        }
 
-        // THEN
-        assert!(result.is_ok());
-        assert_eq!(
-            sqlx::query(item_exists_query)
-                .bind(title2)
-                .bind(ext)
-                .bind(hash2)
-                .fetch_all(&mut connection)
-                .await?
-                .len(),
-            1
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_import_json_skips_an_already_present_hash(ctx: &TempFolder) -> Result<()> {
+        // GIVEN a db that already has an item, and a JSON snapshot re-describing that same hash
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        let hash = item_hash('1');
+        db.import_file("First", &hash, "mp4", MediaKind::Video)
+            .await?;
+        let snapshot = format!(
+            r#"[{{
+            "title":"Renamed","ext":"mp4","hash":"{hash}","media_kind":"video",
+            "tags":["unused"],"future_field":{{"nested":[1,2]}}
+        }}]"#
         );
 
+        // WHEN that snapshot is imported
+        db.import_json(snapshot.as_bytes()).await?;
+
+        // THEN the existing item is left untouched rather than erroring or duplicating
+        let items = db.get_items(&Filter::new()).await?;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "First");
+
         Ok(())
     }
 
     #[test_context(TempFolder)]
     #[tokio::test]
-    async fn test_get_items(ctx: &TempFolder) -> Result<()> {
-        // GIVEN
+    async fn test_import_json_rejects_a_path_traversal_hash(ctx: &TempFolder) -> Result<()> {
+        // GIVEN a JSON snapshot whose hash escapes `thumbnail::path_for`'s `hash[0..2]`/`hash[2..]`
+        // slicing instead of being a real item hash
         let db_path = ctx.path.join("vorg.db");
         let mut db = DB::new(&db_path).await.unwrap();
+        let snapshot = br#"[{
+            "title":"Evil","ext":"mp4","hash":"../../../../../../home/user/.ssh/authorized_keys",
+            "media_kind":"video","tags":[]
+        }]"#;
 
-        // Import file
-        let title = "Test title";
-        let ext = "mp4";
-        let hash = "09c683231bb0e88e84a8408fdbfe174c70d83d03e0604eb612631e79";
-        let result = db.import_file(&title, &hash, &ext).await;
-        assert!(result.is_ok());
+        // WHEN that snapshot is imported
+        let result = db.import_json(snapshot.as_slice()).await;
 
-        // WHEN
-        let items = db.get_items().await?;
+        // THEN it is rejected before ever reaching the db
+        assert!(matches!(result, Err(e) if e.kind == ErrorKind::InvalidJson));
+        assert_eq!(db.get_items(&Filter::new()).await?.len(), 0);
+
+        Ok(())
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn test_import_json_rejects_a_too_short_hash(ctx: &TempFolder) -> Result<()> {
+        // GIVEN a JSON snapshot whose hash is shorter than `ITEM_HASH_HEX_LEN`, which would
+        // otherwise panic `thumbnail::path_for`'s `hash[0..2]` slice
+        let db_path = ctx.path.join("vorg.db");
+        let mut db = DB::new(&db_path).await.unwrap();
+        let snapshot = br#"[{
+            "title":"Evil","ext":"mp4","hash":"a","media_kind":"video","tags":[]
+        }]"#;
+
+        // WHEN that snapshot is imported
+        let result = db.import_json(snapshot.as_slice()).await;
+
+        // THEN it is rejected before ever reaching the db
+        assert!(matches!(result, Err(e) if e.kind == ErrorKind::InvalidJson));
+        assert_eq!(db.get_items(&Filter::new()).await?.len(), 0);
 
-        // THEN
-        assert_eq!(items.len(), 1);
-        assert_eq!(items[0].title, title);
-        assert_eq!(items[0].ext, ext);
-        assert_eq!(items[0].hash, hash);
-        assert_eq!(items[0].tags.len(), 1);
-        assert_eq!(items[0].tags[0], "meta:Incomplete");
         Ok(())
     }
 }
@@ -1,13 +1,21 @@
 #![allow(clippy::module_name_repetitions)]
+use crate::l10n;
 use std::{error, fmt};
 
+/// An error produced by vorg.
+///
+/// `kind` is the coarse category callers match on (`ErrorKind::Duplicate`, etc). `id` selects
+/// which localized message template renders the error; several call sites sharing one `kind`
+/// (e.g. the various `ErrorKind::Archive` failures) use distinct `id`s so each gets its own
+/// wording. `args` are the named values substituted into that template.
 #[derive(Debug)]
 pub struct Error {
-    pub msg: String,
     pub kind: ErrorKind,
+    pub(crate) id: &'static str,
+    pub(crate) args: Vec<(&'static str, String)>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ErrorKind {
     /// The requested file is not found.
     FileNotFound,
@@ -17,6 +25,8 @@ pub enum ErrorKind {
     ThumbnailFolder,
     /// Errors emitted by libmagic.
     Magic,
+    /// Thumbnail generation failed.
+    Thumbnail,
     /// Generic IO errors.
     IO,
     /// Database errors.
@@ -25,35 +35,60 @@ pub enum ErrorKind {
     Unsupported,
     /// The item to import exists already in the repo.
     Duplicate,
+    /// A `.vorg-import` sidecar manifest could not be parsed.
+    InvalidManifest,
+    /// A portable archive is malformed, or failed its integrity check on import.
+    Archive,
+    /// A `Repo::list_items` pagination cursor is malformed.
+    InvalidCursor,
+    /// A `query::parse` filter expression is malformed.
+    InvalidQuery,
+    /// A `DB::import_json` snapshot is malformed, or missing a required field.
+    InvalidJson,
     /// Wrong arguments to the commandline util.
     WrongArguments,
 }
 
+impl Error {
+    /// Constructs an error with no message arguments, e.g. for templates that are entirely
+    /// static text.
+    pub fn new(kind: ErrorKind, id: &'static str) -> Self {
+        Error {
+            kind,
+            id,
+            args: Vec::new(),
+        }
+    }
+
+    /// Constructs an error, attaching named arguments used to render its message.
+    pub fn with_args(kind: ErrorKind, id: &'static str, args: Vec<(&'static str, String)>) -> Self {
+        Error { kind, id, args }
+    }
+}
+
 impl error::Error for Error {}
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.msg)
+        write!(f, "{}", l10n::message(self.id, &self.args))
     }
 }
 
 impl From<sqlx::Error> for Error {
     fn from(value: sqlx::Error) -> Self {
-        Error {
-            msg: value.to_string(),
-            kind: ErrorKind::DB,
-        }
+        Error::with_args(ErrorKind::DB, "db-error", vec![("detail", value.to_string())])
     }
 }
 
 impl From<magic::MagicError> for Error {
     fn from(value: magic::MagicError) -> Self {
-        Error {
-            msg: value.to_string(),
-            kind: ErrorKind::Magic,
-        }
+        Error::with_args(
+            ErrorKind::Magic,
+            "magic-error",
+            vec![("detail", value.to_string())],
+        )
     }
 }
 
@@ -61,9 +96,6 @@ impl From<magic::MagicError> for Error {
 /// error categories.
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
-        Error {
-            msg: value.to_string(),
-            kind: ErrorKind::IO,
-        }
+        Error::with_args(ErrorKind::IO, "io-error", vec![("detail", value.to_string())])
     }
 }
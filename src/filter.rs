@@ -0,0 +1,107 @@
+//! Structured query support for `Repo::get_files`, replacing its `// TODO: Add filtering`.
+//!
+//! `get_files` used to return every `Item` unconditionally. `Filter` lets a caller narrow that
+//! down by tag (present/absent), title substring, `media::MediaKind`, and file extension, pushed
+//! down into `DB::get_items` as SQL rather than filtered in memory. Since `import_file` already
+//! tags every import `"meta:Incomplete"`, `Filter::with_tag("meta:Incomplete")` is a natural first
+//! query: it finds items that still need metadata filled in. `pagination::ItemOrder` reuses the
+//! same `Filter` to narrow a paginated `Repo::list_items` listing.
+
+use crate::media::MediaKind;
+
+/// A query over `Item`s. Every condition set on a `Filter` is AND'd together; an empty `Filter`
+/// (`Filter::new`) matches everything.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub(crate) include_tags: Vec<String>,
+    pub(crate) exclude_tags: Vec<String>,
+    pub(crate) title_contains: Option<String>,
+    pub(crate) media_kind: Option<MediaKind>,
+    pub(crate) extension: Option<String>,
+}
+
+impl Filter {
+    /// An unconstrained filter, matching every item. Start here and narrow with the other methods.
+    pub fn new() -> Self {
+        Filter::default()
+    }
+
+    /// Shorthand for `Filter::new().require_tag(tag)`.
+    pub fn with_tag(tag: impl Into<String>) -> Self {
+        Filter::new().require_tag(tag)
+    }
+
+    /// Narrows to items tagged with `tag`. Calling this more than once requires all of the given
+    /// tags (AND).
+    #[must_use]
+    pub fn require_tag(mut self, tag: impl Into<String>) -> Self {
+        self.include_tags.push(tag.into());
+        self
+    }
+
+    /// Narrows to items NOT tagged with `tag`. Calling this more than once excludes all of the
+    /// given tags.
+    #[must_use]
+    pub fn exclude_tag(mut self, tag: impl Into<String>) -> Self {
+        self.exclude_tags.push(tag.into());
+        self
+    }
+
+    /// Narrows to items whose title contains `substring`.
+    #[must_use]
+    pub fn title_contains(mut self, substring: impl Into<String>) -> Self {
+        self.title_contains = Some(substring.into());
+        self
+    }
+
+    /// Narrows to items of the given `MediaKind`.
+    #[must_use]
+    pub fn of_kind(mut self, kind: MediaKind) -> Self {
+        self.media_kind = Some(kind);
+        self
+    }
+
+    /// Narrows to items whose file extension is exactly `extension` (e.g. `"mp4"`).
+    #[must_use]
+    pub fn of_extension(mut self, extension: impl Into<String>) -> Self {
+        self.extension = Some(extension.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_filter_has_no_conditions() {
+        let filter = Filter::new();
+        assert!(filter.include_tags.is_empty());
+        assert!(filter.exclude_tags.is_empty());
+        assert!(filter.title_contains.is_none());
+        assert!(filter.media_kind.is_none());
+        assert!(filter.extension.is_none());
+    }
+
+    #[test]
+    fn with_tag_requires_that_tag() {
+        let filter = Filter::with_tag("meta:Incomplete");
+        assert_eq!(filter.include_tags, vec!["meta:Incomplete"]);
+    }
+
+    #[test]
+    fn builder_methods_combine() {
+        let filter = Filter::new()
+            .require_tag("a")
+            .require_tag("b")
+            .exclude_tag("c")
+            .title_contains("cats")
+            .of_kind(MediaKind::Image)
+            .of_extension("png");
+        assert_eq!(filter.include_tags, vec!["a", "b"]);
+        assert_eq!(filter.exclude_tags, vec!["c"]);
+        assert_eq!(filter.title_contains.as_deref(), Some("cats"));
+        assert_eq!(filter.media_kind, Some(MediaKind::Image));
+        assert_eq!(filter.extension.as_deref(), Some("png"));
+    }
+}
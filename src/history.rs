@@ -0,0 +1,90 @@
+//! Append-only change log backing `DB::changes_since`/`DB::latest_seq`, recorded in the `changes`
+//! table in the same transaction as the write it describes.
+//!
+//! Modeled on atuin's append-only KV store: rather than diffing two catalogs to sync or undo,
+//! the mutations `import_file`/`import_file_chunked`, `add_tag_to_collection` and `delete_item`
+//! append `Change` rows (an import appends one `AddItem` plus one `AddTag` per tag;
+//! `update_item_extension` records none), and a remote only needs `changes_since` its last known `seq` to catch
+//! up. `parent_seq` links each change to what was the log's tip when it was recorded, forming a
+//! chain a sync client can walk to detect it has diverged (its last-known tip no longer matches
+//! the chain) rather than silently replaying onto the wrong history.
+
+/// Category of mutation a `Change` records. Mirrors the write APIs that produce one:
+/// `import_file`/`import_file_chunked` (`AddItem`), `delete_item` (`RemoveItem`), and
+/// `add_tag_to_collection` (`AddTag`). `RemoveTag` has no caller yet — there is no tag-removal
+/// API today — but is part of the schema now so it doesn't need its own migration later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeOperation {
+    AddItem,
+    RemoveItem,
+    AddTag,
+    RemoveTag,
+}
+
+impl ChangeOperation {
+    /// String stored in `changes.operation`.
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            ChangeOperation::AddItem => "add_item",
+            ChangeOperation::RemoveItem => "remove_item",
+            ChangeOperation::AddTag => "add_tag",
+            ChangeOperation::RemoveTag => "remove_tag",
+        }
+    }
+
+    /// Parses a value previously produced by `as_db_str`.
+    pub fn from_db_str(value: &str) -> Option<Self> {
+        match value {
+            "add_item" => Some(ChangeOperation::AddItem),
+            "remove_item" => Some(ChangeOperation::RemoveItem),
+            "add_tag" => Some(ChangeOperation::AddTag),
+            "remove_tag" => Some(ChangeOperation::RemoveTag),
+            _ => None,
+        }
+    }
+}
+
+/// One row of the append-only `changes` log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Monotonically increasing, gap-free position in the log.
+    pub seq: i64,
+    /// The `seq` that was the log's tip when this change was recorded, or `None` if this was the
+    /// very first change. See the module docs for how a sync client uses this to detect
+    /// divergence.
+    pub parent_seq: Option<i64>,
+    /// Stable per-machine id (see `DB::host_id`) identifying which machine recorded this change.
+    pub host_id: String,
+    pub operation: ChangeOperation,
+    /// The collection (i.e. item) this change applies to, if any.
+    pub collection_id: Option<i64>,
+    /// The tag added or removed, for `ChangeOperation::AddTag`/`RemoveTag`.
+    pub tag: Option<String>,
+    /// Unix timestamp (seconds) this change was recorded.
+    pub created_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_operation_db_str_roundtrips() {
+        for operation in [
+            ChangeOperation::AddItem,
+            ChangeOperation::RemoveItem,
+            ChangeOperation::AddTag,
+            ChangeOperation::RemoveTag,
+        ] {
+            assert_eq!(
+                ChangeOperation::from_db_str(operation.as_db_str()),
+                Some(operation)
+            );
+        }
+    }
+
+    #[test]
+    fn unrecognized_db_str_is_not_an_operation() {
+        assert_eq!(ChangeOperation::from_db_str("rename_item"), None);
+    }
+}
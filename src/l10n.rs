@@ -0,0 +1,94 @@
+//! Localization of CLI output and `Error` messages, modeled on Fluent/l10nregistry.
+//!
+//! Message templates live in `resources/l10n/<locale>/vorg.ftl`, keyed by message id (one id per
+//! distinct error wording, plus `wrong-arguments` for CLI usage). The active locale is resolved
+//! once from `$LC_MESSAGES`/`$LANG`, falling back to `en-US`. Only `en-US` ships today, and it is
+//! the only resource `build_bundle` loads (compiled in with `include_str!`): another locale's
+//! `vorg.ftl` is not picked up unless `build_bundle` loads it too. `Error` construction sites
+//! carry structured arguments rather than pre-formatted strings, so a translation only needs its
+//! own templates.
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use lazy_static::lazy_static;
+use std::env;
+use unic_langid::LanguageIdentifier;
+
+const EN_US_FTL: &str = include_str!("../resources/l10n/en-US/vorg.ftl");
+
+lazy_static! {
+    static ref BUNDLE: FluentBundle<FluentResource> = build_bundle();
+}
+
+fn build_bundle() -> FluentBundle<FluentResource> {
+    let mut bundle = FluentBundle::new(vec![resolve_locale()]);
+    // Plain terminal text: no Unicode isolation marks around substituted arguments.
+    bundle.set_use_isolating(false);
+
+    let resource = FluentResource::try_new(EN_US_FTL.to_owned())
+        .expect("Built-in en-US Fluent resource must parse.");
+    bundle
+        .add_resource(resource)
+        .expect("Built-in en-US Fluent resource must not redefine any message id.");
+
+    bundle
+}
+
+/// Resolves the active locale from `$LC_MESSAGES`/`$LANG`, falling back to `en-US` if unset,
+/// unparseable, or not a locale we ship a resource for.
+fn resolve_locale() -> LanguageIdentifier {
+    let en_us: LanguageIdentifier = "en-US".parse().expect("en-US must be a valid language id.");
+    env::var("LC_MESSAGES")
+        .or_else(|_| env::var("LANG"))
+        .ok()
+        // POSIX locale strings look like "en_US.UTF-8"; Fluent wants "en-US".
+        .and_then(|raw| raw.split('.').next().map(|tag| tag.replace('_', "-")))
+        .and_then(|tag| tag.parse::<LanguageIdentifier>().ok())
+        // `build_bundle` only has an en-US resource.
+        .filter(|langid| *langid == en_us)
+        .unwrap_or(en_us)
+}
+
+/// Renders the message with id `id`, substituting `args` as named Fluent arguments.
+///
+/// Falls back to `id` itself (so callers can at least see which template was missing) if the
+/// active bundle has no such message, or to the unresolved pattern if formatting produced errors.
+pub(crate) fn message(id: &str, args: &[(&'static str, String)]) -> String {
+    let Some(msg) = BUNDLE.get_message(id) else {
+        return id.to_string();
+    };
+    let Some(pattern) = msg.value() else {
+        return id.to_string();
+    };
+
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, FluentValue::from(value.clone()));
+    }
+
+    let mut errors = Vec::new();
+    let rendered = BUNDLE.format_pattern(pattern, Some(&fluent_args), &mut errors);
+    rendered.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_static_message() {
+        assert_eq!(message("duplicate", &[]), "The item to import already exists in the database.");
+    }
+
+    #[test]
+    fn renders_message_with_argument() {
+        assert_eq!(
+            message("file-not-found", &[("path", String::from("a.mp4"))]),
+            "The file to import cannot be found: a.mp4."
+        );
+    }
+
+    #[test]
+    fn unknown_id_falls_back_to_itself() {
+        assert_eq!(message("does-not-exist", &[]), "does-not-exist");
+    }
+}
@@ -1,35 +1,149 @@
+mod archive;
+mod chunking;
+mod database;
 mod db;
 mod error;
+mod fetch;
+mod filter;
+mod history;
+mod l10n;
+mod manifest;
+mod media;
+mod metadata;
+mod pagination;
+mod postgres_database;
+mod query;
+mod reaper;
+mod repair;
+mod report;
+mod store;
+mod storyboard;
 mod thumbnail;
 mod utils;
 
-use lazy_static::lazy_static;
 use sha2::{Digest, Sha224};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashSet, VecDeque},
     fs, io,
     path::Path,
     path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
+use tokio::sync::Semaphore;
 
+use database::Database;
 use db::DB;
+use manifest::Manifest;
+use store::{LocalFsStore, Store};
 
-pub use db::Item;
+pub use archive::ArchiveImportSummary;
+pub use db::{Item, QueryPage};
 pub use error::{Error, ErrorKind, Result};
+pub use fetch::{Fetcher, LocalFileFetcher, RemoteMetadata, StreamFilter, StreamFormat};
+pub use filter::Filter;
+pub use media::MediaKind;
+pub use metadata::MediaMetadata;
+pub use pagination::{ItemOrder, ItemPage};
+pub use postgres_database::PostgresDatabase;
+pub use query::{parse as parse_query, FilterExpr};
+pub use reaper::{spawn_auto_sweep, AutoSweepHandle, SweepSummary};
+pub use repair::RepairSummary;
+pub use report::{to_json, to_text, Finding, FindingCategory};
+pub use store::MemoryStore;
+pub use storyboard::{StoryboardHandle, StoryboardOptions, StoryboardSidecar};
+
+/// Bound on how many chunks `check_data_integrity` re-hashes concurrently.
+const INTEGRITY_CHECK_CONCURRENCY: usize = 8;
+
+/// Chunk-level dedup outcome of a `Repo::import` call, so callers can report storage savings.
+///
+/// Items are content-addressed at the chunk level (see `chunking`): a chunk already referenced by
+/// another item is never written to the `Store` twice, and its `refcount` in the `chunks` table is
+/// bumped instead (see `Tx::upsert_chunk`). This is the caller-visible half of that mechanism.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImportOutcome {
+    /// Chunks newly written to the store by this import.
+    pub chunks_written: usize,
+    /// Chunks this import reused because their content already existed in the store.
+    pub chunks_deduplicated: usize,
+}
 
-lazy_static! {
-    /// Maps from supported MIME types from their default extension
-    static ref SUPPORTED_MIMETYPES: HashMap<&'static str, &'static str> = {
-        let mut supported_mimetypes = HashMap::new();
-        supported_mimetypes.insert("video/mp4", "mp4");
-        supported_mimetypes
-    };
+impl ImportOutcome {
+    fn merge(&mut self, other: ImportOutcome) {
+        self.chunks_written += other.chunks_written;
+        self.chunks_deduplicated += other.chunks_deduplicated;
+    }
+}
+
+/// Seconds since the Unix epoch. Duplicates `db`'s own private helper of the same name rather
+/// than exposing it crate-wide, the same duplicate-rather-than-abstract tradeoff `ReadDb` makes
+/// for its read methods.
+fn now_unix_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
 }
 
 pub struct Repo {
-    db: DB,
+    db: Box<dyn Database>,
+    /// Whether `db` is still the default embedded-SQLite `DB` that `create_repo`/`validate_repo`
+    /// always open at `<repo>/vorg.db`, as opposed to a backend swapped in via `with_database`
+    /// (e.g. `PostgresDatabase`). `<repo>/vorg.db` is created unconditionally before a caller ever
+    /// gets a chance to call `with_database`, so its mere existence on disk can't be used to tell
+    /// the two cases apart — `export`/`import_archive` key off this flag instead of `db_path.is_file()`
+    /// so they don't bundle/overwrite an orphaned SQLite stub for a Postgres-backed repo.
+    uses_default_db: bool,
     path: PathBuf,
+    store: Arc<dyn Store>,
+    thumbnail_path: PathBuf,
+    thumbnail_size: u32,
+    storyboard_options: storyboard::StoryboardOptions,
     magic_cookie: magic::Cookie,
+    online: bool,
+    incomplete_ttl: Duration,
+    sweep_grace_period: Duration,
+}
+
+/// Options controlling how a `Repo` is opened.
+///
+/// Defaults to `online: true`, i.e. full access to the store backend,
+/// `thumbnail_size: thumbnail::DEFAULT_THUMBNAIL_SIZE`, and
+/// `storyboard_options: storyboard::StoryboardOptions::default()`.
+pub struct RepoOptions {
+    /// When `false`, `Repo` avoids touching the store backend for anything that can be answered
+    /// from the local cache file (`store.cache`) instead. `check_data_integrity` then reports
+    /// cache-only findings rather than fully re-verifying chunk content.
+    ///
+    /// Useful for slow or network-mounted repos where a full store walk is expensive.
+    pub online: bool,
+    /// Longest edge, in pixels, that thumbnails generated on import are scaled to. See
+    /// `thumbnail::generate_video` and `thumbnail::generate_image`.
+    pub thumbnail_size: u32,
+    /// Tile count/size for the scrub-preview storyboard sheet generated on import. See
+    /// `storyboard::generate`.
+    pub storyboard_options: storyboard::StoryboardOptions,
+    /// How long a `meta:Incomplete` item is given before `Repo::sweep` treats it as abandoned
+    /// rather than merely unfinished, and deletes it.
+    pub incomplete_ttl: Duration,
+    /// How long `Repo::sweep` waits, after first finding a chunk present in the store but absent
+    /// from the db, before re-checking and actually deleting it. See `Repo::sweep`'s doc comment
+    /// for why this window exists: `import_file` writes a chunk's blob to the store before its db
+    /// transaction commits, so a chunk written moments ago by a still-in-flight import can look
+    /// exactly like an orphan until this grace period gives that import time to finish.
+    pub sweep_grace_period: Duration,
+}
+
+impl Default for RepoOptions {
+    fn default() -> Self {
+        RepoOptions {
+            online: true,
+            thumbnail_size: thumbnail::DEFAULT_THUMBNAIL_SIZE,
+            storyboard_options: storyboard::StoryboardOptions::default(),
+            incomplete_ttl: Duration::from_secs(24 * 60 * 60),
+            sweep_grace_period: Duration::from_secs(5 * 60),
+        }
+    }
 }
 
 impl Repo {
@@ -39,6 +153,8 @@ impl Repo {
     /// If the provided path exists, it performs basic checks to make sure the repo is valid.
     /// For more thorough checks on repo integrity, see `check_data_integrity`.
     ///
+    /// Equivalent to `Repo::new_with_options(path, RepoOptions::default())`.
+    ///
     /// # Errors
     ///
     /// - `ErrorKind::IO` if repo does not exist (determined by existence of vorg.db) and vorg
@@ -50,6 +166,17 @@ impl Repo {
     ///   and failed to be created.
     /// - `ErrorKind::Magic` if failed to initialize libmagic.
     pub async fn new<T>(path: T) -> Result<Self>
+    where
+        T: AsRef<Path>,
+    {
+        Repo::new_with_options(path, RepoOptions::default()).await
+    }
+
+    /// Like `Repo::new`, but lets the caller put the repo in offline mode. See `RepoOptions`.
+    ///
+    /// # Errors
+    /// Same as `Repo::new`.
+    pub async fn new_with_options<T>(path: T, options: RepoOptions) -> Result<Self>
     where
         T: AsRef<Path>,
     {
@@ -59,14 +186,14 @@ impl Repo {
         fs::create_dir_all(path)?;
         if path.join("vorg.db").is_file() {
             // Repo exists, validate it
-            Repo::validate_repo(path).await
+            Repo::validate_repo(path, options).await
         } else {
             // Repo doesn't exist, create it
-            Repo::create_repo(path).await
+            Repo::create_repo(path, options).await
         }
     }
 
-    async fn create_repo<T>(repo_path: T) -> Result<Self>
+    async fn create_repo<T>(repo_path: T, options: RepoOptions) -> Result<Self>
     where
         T: AsRef<Path>,
     {
@@ -83,12 +210,20 @@ impl Repo {
         // Create DB
         Ok(Repo {
             path: repo_path.to_owned(),
-            db: DB::new(repo_path.join("vorg.db")).await?,
+            store: Arc::new(LocalFsStore::new(store_path.join("chunks"))),
+            thumbnail_path,
+            thumbnail_size: options.thumbnail_size,
+            storyboard_options: options.storyboard_options,
+            db: Box::new(DB::new(repo_path.join("vorg.db")).await?),
+            uses_default_db: true,
             magic_cookie: Repo::init_magic()?,
+            online: options.online,
+            incomplete_ttl: options.incomplete_ttl,
+            sweep_grace_period: options.sweep_grace_period,
         })
     }
 
-    async fn validate_repo<T>(repo_path: T) -> Result<Self>
+    async fn validate_repo<T>(repo_path: T, options: RepoOptions) -> Result<Self>
     where
         T: AsRef<Path>,
     {
@@ -97,35 +232,56 @@ impl Repo {
         // Create store
         let store_path = repo_path.join("store");
         if !store_path.is_dir() {
-            return Err(Error {
-                msg: format!(
-                    "File store does not exist or is not a directory at {}.",
-                    store_path.display()
-                ),
-                kind: ErrorKind::StoreFolder,
-            });
+            return Err(Error::with_args(
+                ErrorKind::StoreFolder,
+                "store-folder",
+                vec![("path", store_path.display().to_string())],
+            ));
         }
 
         // Create thumbnail store
         let thumbnail_path = repo_path.join("thumbnail");
         if !thumbnail_path.is_dir() {
-            return Err(Error {
-                msg: format!(
-                    "Thumbnail store does not exist or is not a directory at {}.",
-                    thumbnail_path.display()
-                ),
-                kind: ErrorKind::ThumbnailFolder,
-            });
+            return Err(Error::with_args(
+                ErrorKind::ThumbnailFolder,
+                "thumbnail-folder",
+                vec![("path", thumbnail_path.display().to_string())],
+            ));
         }
 
         // Create DB
         Ok(Repo {
             path: repo_path.to_owned(),
-            db: DB::new(repo_path.join("vorg.db")).await?,
+            store: Arc::new(LocalFsStore::new(store_path.join("chunks"))),
+            thumbnail_path,
+            thumbnail_size: options.thumbnail_size,
+            storyboard_options: options.storyboard_options,
+            db: Box::new(DB::new(repo_path.join("vorg.db")).await?),
+            uses_default_db: true,
             magic_cookie: Repo::init_magic()?,
+            online: options.online,
+            incomplete_ttl: options.incomplete_ttl,
+            sweep_grace_period: options.sweep_grace_period,
         })
     }
 
+    /// Swaps in a different `Store` backend, e.g. `MemoryStore` for hermetic tests that would
+    /// otherwise need a real temp directory.
+    #[must_use]
+    pub fn with_store(mut self, store: impl Store + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// Swaps in a different `Database` backend, e.g. `PostgresDatabase` to share one catalog
+    /// across machines instead of the default embedded SQLite file.
+    #[must_use]
+    pub fn with_database(mut self, database: impl Database + 'static) -> Self {
+        self.db = Box::new(database);
+        self.uses_default_db = false;
+        self
+    }
+
     fn init_magic() -> Result<magic::Cookie> {
         let cookie =
             magic::Cookie::open(magic::CookieFlags::ERROR | magic::CookieFlags::MIME_TYPE)?;
@@ -143,7 +299,9 @@ impl Repo {
     ///
     /// If `path` points to a file, the file will be imported.
     /// If `path` points to a folder, all supported files within the folder will be recursively
-    /// imported.
+    /// imported. If the folder contains a `.vorg-import` manifest, files it mentions are imported
+    /// with the title/tags it specifies instead of the usual placeholder title and
+    /// `"meta:Incomplete"` tag; files it doesn't mention are unaffected. See `manifest::Manifest`.
     ///
     /// # Errors
     ///
@@ -156,40 +314,43 @@ impl Repo {
     /// If `file_path` points to a folder,
     /// Only `ErrorKind::FileNotFound` and `ErrorKind::IO` are returned. The other two types are
     /// suppressed. See stderr if those errors need to be known.
-    pub async fn import<T>(&mut self, file_path: T) -> Result<()>
+    ///
+    /// Returns an `ImportOutcome` tallying how many of the imported chunks were newly written to
+    /// the store versus deduplicated against content already there (summed across every file, if
+    /// `path` is a folder), so a caller can report storage savings.
+    pub async fn import<T>(&mut self, file_path: T) -> Result<ImportOutcome>
     where
         T: AsRef<Path>,
     {
         let file_path = file_path.as_ref();
 
         if !file_path.exists() {
-            return Err(Error {
-                msg: format!(
-                    "The file to import cannot be found: {}.",
-                    file_path.display()
-                ),
-                kind: ErrorKind::FileNotFound,
-            });
+            return Err(Error::with_args(
+                ErrorKind::FileNotFound,
+                "file-not-found",
+                vec![("path", file_path.display().to_string())],
+            ));
         }
 
         if file_path.is_dir() {
             // Folder recursive import
-            self.import_dir(file_path).await?;
+            self.import_dir(file_path).await
         } else {
             // Single file
-            self.import_file(file_path).await?;
+            self.import_file(file_path, None).await
         }
-
-        Ok(())
     }
 
-    async fn import_dir<T>(&mut self, dir: T) -> Result<()>
+    async fn import_dir<T>(&mut self, dir: T) -> Result<ImportOutcome>
     where
         T: AsRef<Path>,
     {
         let dir = dir.as_ref().to_owned();
+        let manifest = Manifest::load(&dir)?;
+
+        let mut outcome = ImportOutcome::default();
         let mut dir_stack = VecDeque::new();
-        dir_stack.push_front(dir);
+        dir_stack.push_front(dir.clone());
         while let Some(current_dir) = dir_stack.pop_front() {
             for entry in fs::read_dir(current_dir).expect("Error opening directory.") {
                 let entry = entry.expect("Error getting entry in directory.");
@@ -197,15 +358,18 @@ impl Repo {
                 if path.is_dir() {
                     dir_stack.push_front(path);
                 } else {
-                    let Err(error) = self.import_file(&path).await else {
-                        continue;
-                    };
-                    match error.kind {
-                        ErrorKind::IO => {
+                    let manifest_entry = manifest.as_ref().and_then(|manifest| {
+                        path.strip_prefix(&dir)
+                            .ok()
+                            .and_then(|relative_path| manifest.entry_for(relative_path))
+                    });
+                    match self.import_file(&path, manifest_entry).await {
+                        Ok(file_outcome) => outcome.merge(file_outcome),
+                        Err(error) if error.kind == ErrorKind::IO => {
                             // Do not suppress IO error, as those indicate import failure.
                             return Err(error);
                         }
-                        _ => {
+                        Err(error) => {
                             // Suppress all other errors, since those are either unsupported or
                             // duplicates.
                             eprintln!("Error encountered: {error}. Ignoring.");
@@ -214,209 +378,752 @@ impl Repo {
                 }
             }
         }
-        Ok(())
+        Ok(outcome)
     }
 
-    async fn import_file<T>(&mut self, file: T) -> Result<()>
+    async fn import_file<T>(
+        &mut self,
+        file: T,
+        manifest_entry: Option<&manifest::ManifestEntry>,
+    ) -> Result<ImportOutcome>
     where
         T: AsRef<Path>,
     {
         let file = file.as_ref();
 
-        // Check file type
+        // Check file type and dispatch to its media handler
         let mime_type = self
             .magic_cookie
             .file(file)
             .expect("Libmagic ffi should not fail.");
-        let mime_result = SUPPORTED_MIMETYPES.get(mime_type.as_str());
-        if mime_result.is_none() {
-            return Err(Error {
-                msg: format!(
-                    "The file to import has an supported type: {}.",
-                    file.display()
-                ),
-                kind: ErrorKind::Unsupported,
-            });
-        }
-        let default_extension = *mime_result.unwrap();
+        let Some(media_type) = media::lookup(mime_type.as_str()) else {
+            return Err(Error::with_args(
+                ErrorKind::Unsupported,
+                "unsupported",
+                vec![("path", file.display().to_string())],
+            ));
+        };
 
         // Compute hash
         let hash = Repo::hash(file).unwrap();
 
-        // Use the full file path as placeholder title.
-        let title = file.to_string_lossy().into_owned();
+        // Use the manifest's title/tags if this file was matched by a `.vorg-import` manifest
+        // entry, otherwise fall back to the full file path and a single "meta:Incomplete" tag.
+        let title = manifest_entry.map_or_else(
+            || file.to_string_lossy().into_owned(),
+            |entry| entry.title.clone(),
+        );
+        let tags = manifest_entry
+            .map_or_else(|| vec![String::from("meta:Incomplete")], |entry| entry.tags.clone());
 
         // Get extension
         let ext = file.extension().map_or_else(
-            || String::from(default_extension),
+            || String::from(media_type.default_extension),
             |filename| filename.to_string_lossy().into_owned(),
         );
 
+        // Probe technical metadata (duration, resolution, codecs, ...) concurrently with the
+        // chunking step below: both just read the file, so there's no need to serialize them.
+        // Probing never fails the import; a file `ffmpeg`/`image` can't make sense of just yields
+        // `metadata::MediaMetadata::default()`.
+        let probe_path = file.to_path_buf();
+        let probe_kind = media_type.kind;
+        let metadata_task =
+            tokio::task::spawn_blocking(move || metadata::probe(probe_kind, &probe_path));
+
+        // Split into content-defined chunks so near-identical files (re-encodes, sidecar
+        // variants, appended metadata) share storage with previously imported files.
+        let chunks = chunking::chunk_file(file)?;
+
+        // Check which of this file's chunks already exist in one round-trip rather than one
+        // `chunk_exists` query per chunk.
+        let hashes: Vec<String> = chunks.iter().map(|chunk| chunk.hash.clone()).collect();
+        let already_in_db: HashSet<String> =
+            self.db.chunks_exist(&hashes).await?.into_iter().collect();
+
+        // Write any chunk not already present in the store. This must happen before the db
+        // import below, since that call is what makes a chunk "known" to `chunks_exist`. A chunk
+        // repeated within this same file is deduplicated against its earlier occurrence here too,
+        // rather than being written to the store twice.
+        let mut chunk_sizes = Vec::with_capacity(chunks.len());
+        let mut outcome = ImportOutcome::default();
+        let mut written_this_import = HashSet::new();
+        for chunk in &chunks {
+            if already_in_db.contains(&chunk.hash) || written_this_import.contains(&chunk.hash) {
+                outcome.chunks_deduplicated += 1;
+            } else {
+                self.store.put(&chunk.hash, &chunk.data)?;
+                outcome.chunks_written += 1;
+                written_this_import.insert(chunk.hash.clone());
+            }
+            chunk_sizes.push((chunk.hash.clone(), chunk.data.len() as i64));
+        }
+
+        let metadata = metadata_task.await.unwrap_or_default();
+
         // Import into db
         // This will propagate `ErrorKind::Duplicate` if a duplicate is imported.
-        self.db.import_file(&title, &ext, &hash).await?;
-
-        // Prepare to move into store
-        let store_subfolder = self.path.join("store").join(&hash[0..2]);
-        let store_path = store_subfolder.join(format!("{}.{}", &hash[2..], ext));
-
-        // Check/create store subfolder
-        fs::create_dir(&store_subfolder)?;
-
-        // Attempt rename first.
-        // If source and destination are on different file systems, fallback to copy and remove.
-        if let Err(error) = fs::rename(file, &store_path) {
-            // TODO: when io_error_more is stablized, use ErrorKind::CrossesDevices instead.
-            // This scenario cannot be easily tested. I just tried it and it seems to work.
-            // Avoid importing files from across device boundries is the most prudent choice.
-            if error.to_string().starts_with("Invalid cross-device link") {
-                fs::copy(file, &store_path)?;
-                fs::remove_file(file)?;
-            } else {
-                return Err(Error {
-                    msg: error.to_string(),
-                    kind: ErrorKind::IO,
-                });
+        self.db
+            .import_file_chunked(
+                &title,
+                &hash,
+                &ext,
+                media_type.kind,
+                &tags,
+                &metadata,
+                &chunk_sizes,
+            )
+            .await?;
+
+        // Grab a preview thumbnail while the original file still exists, via whichever strategy
+        // fits this item's media kind. This must happen before the file is removed below, and
+        // must not abort the import: the move into the chunk store has already succeeded, and
+        // not every file has a frame or image vorg can make a thumbnail out of.
+        let thumbnail_result = match media_type.kind {
+            MediaKind::Video => {
+                thumbnail::generate_video(file, &hash, &self.thumbnail_path, self.thumbnail_size)
             }
+            MediaKind::Image => {
+                thumbnail::generate_image(file, &hash, &self.thumbnail_path, self.thumbnail_size)
+            }
+        };
+        if let Err(error) = thumbnail_result {
+            eprintln!("Error encountered: {error}. Ignoring.");
         }
 
-        // TODO: Generate thumbnail
+        // Also generate a scrub-preview storyboard sheet for videos, same best-effort treatment
+        // as the single-still thumbnail above: a file with no usable duration just gets no sheet.
+        if media_type.kind == MediaKind::Video {
+            let storyboard_result =
+                storyboard::generate(file, self.store.as_ref(), &self.storyboard_options);
+            if let Err(error) = storyboard_result {
+                eprintln!("Error encountered: {error}. Ignoring.");
+            }
+        }
 
-        Ok(())
+        // The original file has now been fully absorbed into the chunk store.
+        fs::remove_file(file)?;
+
+        Ok(outcome)
     }
 
     /// Get files that satisfy the given filter.
     ///
-    /// TODO: Add filtering.
-    ///
-    pub async fn get_files(&mut self) -> Result<Vec<Item>> {
-        self.db.get_items().await
-    }
-
-    /**
-     * This function exhaustively checks the integrity of the repository.
-     * Returns a textual description of the errors found, one error per line.
-     * If the repo has no problems, returns an empty string.
-     *
-     * All errors are specified relative to the info found in db.
-     * Three kinds of errors are possible:
-     * store: having more or less files than in db.
-     * hash: hash of the file found in store does not match what's stored in db.
-     * ext: extension found in store is different in db
-     * thumbnail: having thumbnails for more or less files than in db.
-     *
-     * This can be really slow on large repos.
-     * Do not run regularly and do not run on UI thread.
-     */
-    pub async fn check_data_integrity(&mut self) -> Result<String> {
-        let mut result = String::new();
-
-        let db_files = self.db.get_items().await?;
-
-        // Check store
-        let mut store_files = Vec::new();
-        let mut wrong_hash = Vec::new();
-        Repo::check_store_folder(&self.path.join("store"), &mut store_files, &mut wrong_hash)?;
+    /// Pass `Filter::new()` for every item in the repo, or e.g.
+    /// `Filter::with_tag("meta:Incomplete")` to find items still needing metadata after import.
+    pub async fn get_files(&mut self, filter: &Filter) -> Result<Vec<Item>> {
+        self.db.get_items(filter).await
+    }
+
+    /// Returns one page of items that satisfy `filter`, ordered by `order`, for browsing a repo
+    /// too large to pull back with `get_files` in one shot (e.g. for a UI list view).
+    ///
+    /// Pass the previous call's `ItemPage::next_cursor()` as `cursor` to fetch the following page,
+    /// or `None` to start from the beginning. See `pagination` for why this is cursor-based rather
+    /// than page-number based.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    /// - `ErrorKind::InvalidCursor` if `cursor` is set and was not produced by a previous call's
+    ///   `ItemPage::next_cursor()`.
+    pub async fn list_items(
+        &mut self,
+        filter: &Filter,
+        order: ItemOrder,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<ItemPage> {
+        self.db.list_items_page(filter, order, cursor, page_size).await
+    }
+
+    /// Gets items matching `query`, a boolean tag/title expression parsed by `query::parse` (see
+    /// its module doc for the query syntax). Unlike `get_files`'s `Filter`, this can express `OR`
+    /// and `NOT` over tags and titles.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    pub async fn query_items(&mut self, query: &FilterExpr) -> Result<Vec<Item>> {
+        self.db.query_items(query).await
+    }
 
-        // TODO: Check thumbnail
+    /// Returns one page of items matching `query`, alongside the total number of matches; see
+    /// `db::DB::query_items_page` for why this is `limit`/`offset`-based rather than cursor-based
+    /// like `list_items`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    pub async fn query_items_page(
+        &mut self,
+        query: &FilterExpr,
+        limit: usize,
+        offset: usize,
+    ) -> Result<QueryPage> {
+        self.db.query_items_page(query, limit, offset).await
+    }
+
+    /// Exhaustively checks the integrity of the repository.
+    ///
+    /// Returns one `report::Finding` per problem found, in no particular order. If the repo has
+    /// no problems, returns an empty `Vec`. `report::to_text` and `report::to_json` render these
+    /// findings the way `main`'s `check --format text|json` does.
+    ///
+    /// All findings are specified relative to the info found in db. Five kinds are possible
+    /// today:
+    /// - `ChunkMissing`/`ChunkUnexpected`: the store has more or fewer chunks than referenced by
+    ///   db.
+    /// - `ChunkHashMismatch`: a chunk's on-disk content does not hash to its filename.
+    /// - `ThumbnailMissing`: an item has no corresponding file under `thumbnail/`, e.g. because
+    ///   its video had no decodable frame, or thumbnail generation failed at import time.
+    /// - `ExtensionMismatch`: an item's recorded extension disagrees with the one libmagic
+    ///   sniffing would assign its actual content today, e.g. because it was imported under an
+    ///   extension that didn't match its real container.
+    ///
+    /// This re-hashes every chunk in the store, which can still be slow on large repos, so do not
+    /// run it regularly or on a UI thread. The re-hashing itself runs across up to
+    /// `INTEGRITY_CHECK_CONCURRENCY` chunks at once instead of one at a time, and `progress` is
+    /// called as `progress(hashed, total)` after each chunk finishes, so a caller can render a
+    /// progress bar instead of this function printing to stdout itself.
+    ///
+    /// Chunks confirmed clean are recorded in `<repo>/integrity-check.progress` as they complete;
+    /// if the process is interrupted partway through, the next call skips chunks already recorded
+    /// there rather than re-hashing them, at the cost of trusting that their content has not
+    /// changed since. The file is removed once a run completes in full.
+    ///
+    /// When the repo was opened with `RepoOptions { online: false }`, the chunk store is not
+    /// walked or re-hashed at all: the structural findings are instead computed against the last
+    /// cached manifest (see `store.cache`) and marked `verified: false`. No `ChunkHashMismatch` or
+    /// `ExtensionMismatch` findings are produced offline, since those require reading chunk
+    /// content.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if reading the item/chunk list from the db fails.
+    /// - `ErrorKind::IO` if walking the store, reading/writing the progress or cache files, or a
+    ///   chunk read fails.
+    pub async fn check_data_integrity(
+        &mut self,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<Vec<report::Finding>> {
+        let mut findings = Vec::new();
+
+        let db_chunk_hashes = self.db.get_all_chunk_hashes().await?;
+
+        let (store_chunk_hashes, verified) = if self.online {
+            let mut store_chunk_hashes = self.store.list()?;
+            let wrong_hash = self.rehash_store(&store_chunk_hashes, &mut progress).await?;
+            store_chunk_hashes.sort();
+            self.write_store_cache(&store_chunk_hashes)?;
+
+            for (expected_hash, actual_hash) in wrong_hash {
+                findings.push(report::Finding {
+                    category: report::FindingCategory::ChunkHashMismatch,
+                    path: expected_hash.clone(),
+                    expected_hash: Some(expected_hash),
+                    actual_hash: Some(actual_hash),
+                    verified: true,
+                });
+            }
+
+            (store_chunk_hashes, true)
+        } else {
+            (self.read_store_cache()?, false)
+        };
 
         // Process result
-        store_files.sort();
-        let mut i = 0;
-        let mut j = 0;
-        while i < db_files.len() && j < store_files.len() {
-            let db_hash = &db_files[i].hash;
-            let db_ext = &db_files[i].ext;
-            let (store_hash, store_ext) = &store_files[j];
-            if db_hash == store_hash {
-                i += 1;
-                j += 1;
-
-                // Only check ext for full match
-                if db_ext != store_ext {
-                    result.push_str(
-                        format!(
-                            "ext: different extensions: {db_ext} in db but {store_ext} in store\n",
-                        )
-                        .as_str(),
-                    );
-                }
+        let (missing, unexpected) =
+            utils::reconcile_sorted_hashes(&db_chunk_hashes, &store_chunk_hashes);
+        for hash in missing {
+            findings.push(report::Finding {
+                category: report::FindingCategory::ChunkMissing,
+                path: hash,
+                expected_hash: None,
+                actual_hash: None,
+                verified,
+            });
+        }
+        for hash in unexpected {
+            findings.push(report::Finding {
+                category: report::FindingCategory::ChunkUnexpected,
+                path: hash,
+                expected_hash: None,
+                actual_hash: None,
+                verified,
+            });
+        }
+        // Cross-reference every item against the thumbnail it should have. Unlike chunks, the
+        // thumbnail store is always local, so this is cheap enough to run whether or not the repo
+        // was opened offline.
+        for item in self.db.get_items(&Filter::new()).await? {
+            if !thumbnail::path_for(&self.thumbnail_path, &item.hash).is_file() {
+                findings.push(report::Finding {
+                    category: report::FindingCategory::ThumbnailMissing,
+                    path: item.hash.clone(),
+                    expected_hash: None,
+                    actual_hash: None,
+                    verified: true,
+                });
+            }
 
-                continue;
+            // Re-derives the item's extension from its actual content, the same way import
+            // assigned one in the first place. Requires reading chunk content, so skipped offline.
+            if self.online {
+                if let Some(real_ext) = self.detect_extension_mismatch(&item).await? {
+                    findings.push(report::Finding {
+                        category: report::FindingCategory::ExtensionMismatch,
+                        path: item.hash,
+                        expected_hash: Some(item.ext),
+                        actual_hash: Some(real_ext),
+                        verified: true,
+                    });
+                }
             }
-            if db_hash < store_hash {
-                i += 1;
-                result.push_str(format!("store: file not found in store: {db_hash}\n").as_str());
-                continue;
+        }
+
+        Ok(findings)
+    }
+
+    /// Re-hashes every chunk in `store_chunk_hashes` against its actual content, across up to
+    /// `INTEGRITY_CHECK_CONCURRENCY` chunks at once, skipping any already recorded clean in
+    /// `integrity_progress_path` by an interrupted previous run. Returns the `(expected, actual)`
+    /// pairs for chunks whose content no longer hashes to their filename.
+    async fn rehash_store(
+        &self,
+        store_chunk_hashes: &[String],
+        progress: &mut impl FnMut(usize, usize),
+    ) -> Result<Vec<(String, String)>> {
+        let already_verified: HashSet<String> = fs::read_to_string(self.integrity_progress_path())
+            .map(|contents| contents.lines().map(String::from).collect())
+            .unwrap_or_default();
+        let to_hash: Vec<String> = store_chunk_hashes
+            .iter()
+            .filter(|hash| !already_verified.contains(*hash))
+            .cloned()
+            .collect();
+        let total = to_hash.len();
+
+        let semaphore = Arc::new(Semaphore::new(INTEGRITY_CHECK_CONCURRENCY));
+        let tasks: Vec<_> = to_hash
+            .into_iter()
+            .map(|expected_hash| {
+                let store = Arc::clone(&self.store);
+                let semaphore = Arc::clone(&semaphore);
+                tokio::spawn(async move {
+                    let _permit = semaphore
+                        .acquire_owned()
+                        .await
+                        .expect("semaphore should never be closed");
+                    let data = store.get(&expected_hash)?;
+                    let actual_hash = chunking::hash_chunk(&data);
+                    Ok::<_, Error>((expected_hash, actual_hash))
+                })
+            })
+            .collect();
+
+        let mut wrong_hash = Vec::new();
+        for (hashed, task) in tasks.into_iter().enumerate() {
+            let (expected_hash, actual_hash) = task.await.expect("hashing task panicked")?;
+            if expected_hash == actual_hash {
+                self.append_integrity_progress(&expected_hash)?;
+            } else {
+                wrong_hash.push((expected_hash, actual_hash));
             }
-            // db_hash > store_hash
-            j += 1;
-            result.push_str(format!("store: redundant file in store: {store_hash}\n").as_str());
+            progress(hashed + 1, total);
         }
-        while i < db_files.len() {
-            result.push_str(
-                format!("store: file not found in store: {}\n", &db_files[i].hash).as_str(),
-            );
-            i += 1;
+
+        // The run completed in full: nothing left to resume from next time.
+        let _ = fs::remove_file(self.integrity_progress_path());
+
+        Ok(wrong_hash)
+    }
+
+    /// Path to the incremental resume file `rehash_store` uses, see `check_data_integrity`.
+    fn integrity_progress_path(&self) -> PathBuf {
+        self.path.join("integrity-check.progress")
+    }
+
+    /// Reassembles `item`'s chunks and re-derives its extension the same way `import_file` first
+    /// assigned one (libmagic mimetype sniffing against `media::lookup`), returning `Some(ext)` if
+    /// it disagrees with `item.ext`.
+    ///
+    /// Returns `None` (no finding) rather than erroring if a chunk can no longer be read (that's
+    /// already reported separately as `ChunkMissing`/`ChunkHashMismatch`) or if the reassembled
+    /// content no longer matches any media type vorg recognizes.
+    async fn detect_extension_mismatch(&mut self, item: &Item) -> Result<Option<String>> {
+        let Some(chunk_hashes) = self.db.get_item_chunk_hashes(&item.hash).await? else {
+            return Ok(None);
+        };
+        let mut data = Vec::new();
+        for chunk_hash in &chunk_hashes {
+            let Ok(chunk_data) = self.store.get(chunk_hash) else {
+                return Ok(None);
+            };
+            data.extend_from_slice(&chunk_data);
         }
-        while j < store_files.len() {
-            result.push_str(
-                format!("store: redundant file in store: {}\n", &store_files[j].0).as_str(),
-            );
-            j += 1;
+
+        let temp_path = std::env::temp_dir().join(format!("vorg-integrity-{}", item.hash));
+        fs::write(&temp_path, &data)?;
+        let mime_type = self.magic_cookie.file(&temp_path);
+        let _ = fs::remove_file(&temp_path);
+        let Ok(mime_type) = mime_type else {
+            return Ok(None);
+        };
+        let Some(media_type) = media::lookup(mime_type.as_str()) else {
+            return Ok(None);
+        };
+
+        if media_type.default_extension == item.ext {
+            Ok(None)
+        } else {
+            Ok(Some(media_type.default_extension.to_string()))
         }
-        for error in wrong_hash {
-            result.push_str(format!("hash: {error}\n").as_str());
+    }
+
+    /// Records that `hash` has been confirmed clean this run, so a future interrupted-run resume
+    /// can skip re-hashing it.
+    fn append_integrity_progress(&self, hash: &str) -> Result<()> {
+        use std::io::Write;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.integrity_progress_path())?;
+        writeln!(file, "{hash}")?;
+        Ok(())
+    }
+
+    /// Path to the local manifest cache used by offline repos.
+    fn store_cache_path(&self) -> PathBuf {
+        self.path.join("store.cache")
+    }
+
+    /// Persists the current chunk store manifest (one hash per line) so future offline opens can
+    /// use it without walking the store.
+    fn write_store_cache(&self, store_chunk_hashes: &[String]) -> Result<()> {
+        fs::write(self.store_cache_path(), store_chunk_hashes.join("\n"))?;
+        Ok(())
+    }
+
+    /// Reads back the manifest written by `write_store_cache`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::IO` if no cache has been written yet, e.g. `check_data_integrity` has never
+    ///   run online on this repo.
+    fn read_store_cache(&self) -> Result<Vec<String>> {
+        let contents = fs::read_to_string(self.store_cache_path())?;
+        Ok(contents
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    /// Repairs problems surfaced by `check_data_integrity`.
+    ///
+    /// Repeatedly re-runs the check and acts on what it reports:
+    /// - `ChunkUnexpected` chunks (present on disk, absent from db) are quarantined into
+    ///   `<repo>/quarantine`, then deleted from the store.
+    /// - `ChunkHashMismatch` chunks are quarantined into `<repo>/corrupted` (keyed by the hash db
+    ///   expected) and deleted from the store, rather than left under a filename that no longer
+    ///   matches their content; still recorded in the returned summary for manual review, since
+    ///   the original content cannot be regenerated.
+    /// - `ExtensionMismatch` items have their recorded extension updated to the one re-derived
+    ///   from their actual content.
+    /// - `ChunkMissing` and `ThumbnailMissing` findings are recorded in the returned summary for
+    ///   manual review; neither a missing chunk nor a missing thumbnail can be regenerated
+    ///   without the original file.
+    ///
+    /// Stops once the check comes back clean or a pass makes no further progress, capped at
+    /// `repair::MAX_PASSES` passes.
+    ///
+    /// # Errors
+    /// - `ErrorKind::IO` if re-running the check or quarantining a file fails.
+    pub async fn repair(&mut self) -> Result<RepairSummary> {
+        self.repair_impl(false).await
+    }
+
+    /// Like `repair`, but only reports the actions that would be taken (as a unified diff,
+    /// rendered with the `similar` crate) without mutating the repo.
+    ///
+    /// # Errors
+    /// Same as `repair`.
+    pub async fn repair_dry_run(&mut self) -> Result<String> {
+        let summary = self.repair_impl(true).await?;
+        let actions: Vec<String> = summary.passes.into_iter().flatten().collect();
+        Ok(repair::describe_dry_run(&actions))
+    }
+
+    async fn repair_impl(&mut self, dry_run: bool) -> Result<RepairSummary> {
+        let mut summary = RepairSummary::default();
+        // Identity (category, path) of every unresolvable finding already folded into
+        // `summary.unresolved`, so a `ChunkMissing`/`ThumbnailMissing`/`ChunkHashMismatch` finding
+        // that survives from one pass to the next (because it can't be fixed, not because nothing
+        // else changed) is only reported once rather than once per remaining pass.
+        let mut reported_unresolved: HashSet<(report::FindingCategory, String)> = HashSet::new();
+
+        for _ in 0..repair::MAX_PASSES {
+            let findings = self.check_data_integrity(|_, _| {}).await?;
+            if findings.is_empty() {
+                break;
+            }
+
+            let mut actions = Vec::new();
+            for finding in findings {
+                match finding.category {
+                    report::FindingCategory::ChunkUnexpected => {
+                        let hash = finding.path;
+                        let action = format!("quarantine redundant chunk {hash}");
+                        if dry_run {
+                            actions.push(action);
+                            continue;
+                        }
+                        let data = self.store.get(&hash)?;
+                        let quarantine_dir = self.path.join("quarantine");
+                        fs::create_dir_all(&quarantine_dir)?;
+                        fs::write(quarantine_dir.join(&hash), data)?;
+                        self.store.delete(&hash)?;
+                        actions.push(action);
+                    }
+                    report::FindingCategory::ChunkMissing => {
+                        if reported_unresolved.insert((finding.category, finding.path.clone())) {
+                            summary.unresolved.push(format!(
+                                "chunk {} is missing and cannot be regenerated",
+                                finding.path
+                            ));
+                        }
+                    }
+                    report::FindingCategory::ChunkHashMismatch => {
+                        let expected_hash = finding.path;
+                        let actual_hash = finding.actual_hash.clone().unwrap_or_default();
+                        if reported_unresolved.insert((finding.category, expected_hash.clone())) {
+                            summary.unresolved.push(format!(
+                                "Expected chunk {expected_hash}, but real hash is {actual_hash}"
+                            ));
+                        }
+                        // Quarantining below deletes `expected_hash` from the store, which turns
+                        // this same finding into a `ChunkMissing` for `expected_hash` on the next
+                        // pass. Pre-insert that key too, so it's recognized as already reported
+                        // rather than being surfaced as a second, seemingly-unrelated finding.
+                        reported_unresolved
+                            .insert((report::FindingCategory::ChunkMissing, expected_hash.clone()));
+                        let action = format!(
+                            "quarantine corrupted chunk {expected_hash} (real hash {actual_hash}) \
+                             into corrupted/"
+                        );
+                        if dry_run {
+                            actions.push(action);
+                            continue;
+                        }
+                        let data = self.store.get(&expected_hash)?;
+                        let corrupted_dir = self.path.join("corrupted");
+                        fs::create_dir_all(&corrupted_dir)?;
+                        fs::write(corrupted_dir.join(&expected_hash), data)?;
+                        self.store.delete(&expected_hash)?;
+                        actions.push(action);
+                    }
+                    report::FindingCategory::ThumbnailMissing => {
+                        if reported_unresolved.insert((finding.category, finding.path.clone())) {
+                            summary.unresolved.push(format!(
+                                "thumbnail for item {} is missing and cannot be regenerated \
+                                 without the original file",
+                                finding.path
+                            ));
+                        }
+                    }
+                    report::FindingCategory::ExtensionMismatch => {
+                        let item_hash = finding.path;
+                        let db_ext = finding.expected_hash.clone().unwrap_or_default();
+                        let real_ext = finding.actual_hash.clone().unwrap_or_default();
+                        let action = format!(
+                            "update item {item_hash} extension from {db_ext} to {real_ext}"
+                        );
+                        if dry_run {
+                            actions.push(action);
+                            continue;
+                        }
+                        self.db.update_item_extension(&item_hash, &real_ext).await?;
+                        actions.push(action);
+                    }
+                }
+            }
+
+            let made_progress = !actions.is_empty();
+            summary.passes.push(actions);
+            if dry_run || !made_progress {
+                break;
+            }
         }
-        // TODO: add thumbnail errors
 
-        Ok(result)
+        Ok(summary)
     }
 
-    fn check_store_folder<T>(
-        dir_path: T,
-        found_files: &mut Vec<(String, String)>,
-        wrong_hash: &mut Vec<String>,
-    ) -> Result<()>
+    /// Exports this repo to a single, self-contained portable archive (a zip "pod") at
+    /// `archive_path`: every chunk in the store, every thumbnail, and (only if `self` still uses
+    /// the default embedded SQLite backend) the db file, plus a manifest recording each member's
+    /// SHA-256 digest and length so `import_archive` can verify them. See `archive` for the exact
+    /// layout.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if reading the chunk/item hash lists from the db fails.
+    /// - `ErrorKind::IO` if the store, db file, or thumbnails cannot be read, or `archive_path`
+    ///   cannot be written.
+    pub async fn export<T>(&mut self, archive_path: T) -> Result<()>
     where
         T: AsRef<Path>,
     {
-        for entry in fs::read_dir(dir_path).expect("Error opening directory.") {
-            let entry = entry.expect("Error getting entry in directory.");
-            let path = entry.path();
-            if path.is_dir() {
-                Repo::check_store_folder(&path, found_files, wrong_hash)?;
-            } else {
-                let expected_hash = path
-                    .parent()
-                    .expect("Store item must have a parent")
-                    .file_name()
-                    .expect("Store item parent must have a filename.")
-                    .to_string_lossy()
-                    + path
-                        .file_stem()
-                        .expect("Store item must have a filestem.")
-                        .to_string_lossy();
-                let expected_hash = expected_hash.to_string();
-
-                // TODO: remove progress
-                println!("Checking {expected_hash}");
-
-                let real_hash = Repo::hash(&path)?;
-                if expected_hash != real_hash {
-                    wrong_hash.push(format!(
-                        "Expected {expected_hash}, but real hash is {real_hash}"
-                    ));
+        let chunk_hashes = self.store.list()?;
+        let item_hashes = self.db.get_all_item_hashes().await?;
+        let db_path = self.path.join("vorg.db");
+        archive::export(
+            &chunk_hashes,
+            &item_hashes,
+            self.store.as_ref(),
+            self.uses_default_db.then_some(db_path.as_path()),
+            &self.thumbnail_path,
+            archive_path.as_ref(),
+        )
+    }
+
+    /// Imports a portable archive written by `export` into this repo.
+    ///
+    /// Refuses to touch the store if it already holds chunks, unless `merge` is `true`, in which
+    /// case only chunks missing from the store are written (the same "missing"/"unexpected"
+    /// reconciliation `check_data_integrity` uses). The bundled db file is only restored if this
+    /// repo's catalog has no items yet and `self` still uses the default embedded SQLite backend;
+    /// a repo opened with `with_database` pointing elsewhere has nowhere for a restored db file to
+    /// go (its `<repo>/vorg.db` is an unused stub left over from before `with_database` swapped
+    /// `db` out), so its db member is always skipped.
+    ///
+    /// # Errors
+    /// - `ErrorKind::IO` if the store already has chunks and `merge` is `false`.
+    /// - `ErrorKind::DB` if reading the current item list fails.
+    /// - `ErrorKind::Archive` if the archive has no valid manifest, references a member it doesn't
+    ///   contain, or a member's content doesn't match its recorded digest.
+    /// - `ErrorKind::IO` if the archive, db file, or thumbnails cannot be read or written.
+    pub async fn import_archive<T>(
+        &mut self,
+        archive_path: T,
+        merge: bool,
+    ) -> Result<ArchiveImportSummary>
+    where
+        T: AsRef<Path>,
+    {
+        let mut existing_chunk_hashes = self.store.list()?;
+        if !existing_chunk_hashes.is_empty() && !merge {
+            return Err(Error::new(ErrorKind::IO, "archive-store-not-empty"));
+        }
+        existing_chunk_hashes.sort();
+
+        let db_path = self.path.join("vorg.db");
+        let db_is_empty = self.db.get_all_item_hashes().await?.is_empty();
+
+        let summary = archive::import(
+            archive_path.as_ref(),
+            &existing_chunk_hashes,
+            self.store.as_ref(),
+            (self.uses_default_db && db_is_empty).then_some(db_path.as_path()),
+            &self.thumbnail_path,
+        )?;
+
+        // `archive::import` just rewrote `vorg.db`'s file content out from under `self.db`'s open
+        // connection; reopen it against the restored file so the connection's schema/page cache
+        // reflects what's actually on disk rather than the (now stale) catalog it was opened
+        // against. `self.uses_default_db` is checked explicitly rather than trusting
+        // `summary.db_restored` alone: `<repo>/vorg.db` exists on disk even for a repo backed by a
+        // non-default `Database` (see the field doc on `uses_default_db`), so without this guard a
+        // coincidental restore would clobber `self.db` with an unrelated SQLite connection.
+        if summary.db_restored && self.uses_default_db {
+            self.db = Box::new(DB::new(&db_path).await?);
+        }
+
+        Ok(summary)
+    }
+
+    /// Deletes the item identified by `hash` from the catalog, its blob(s) from the store (any
+    /// chunk whose `refcount` reaches zero as a result), and its thumbnail file, if one exists.
+    ///
+    /// Returns the hashes of any chunks reclaimed this way, so `Repo::sweep` can fold them into
+    /// `SweepSummary`. Removing an item's chunks should go through here rather than deleting them
+    /// directly: `Database::delete_item` only decrements refcounts and hands back the hashes that
+    /// dropped to zero, it never touches the store itself (`repair_impl` is the other place that
+    /// deletes store blobs directly, but only for chunks `check_data_integrity` already flagged as
+    /// unexpected or corrupt, never for a chunk an item still references).
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if the underlying query fails.
+    /// - `ErrorKind::IO` if deleting a reclaimed chunk's blob or the thumbnail file fails.
+    pub async fn delete_item(&mut self, hash: &str) -> Result<Vec<String>> {
+        let reclaimed_chunks = self.db.delete_item(hash).await?;
+        for chunk_hash in &reclaimed_chunks {
+            self.store.delete(chunk_hash)?;
+        }
+        let thumbnail_path = thumbnail::path_for(&self.thumbnail_path, hash);
+        if thumbnail_path.is_file() {
+            fs::remove_file(thumbnail_path)?;
+        }
+        Ok(reclaimed_chunks)
+    }
+
+    /// Reclaims storage the repo no longer needs, in two passes:
+    ///
+    /// - Every `Filter::with_tag("meta:Incomplete")` item whose `imported_at` is older than
+    ///   `RepoOptions::incomplete_ttl` is deleted via `Repo::delete_item`, on the assumption that
+    ///   an import left incomplete this long was abandoned rather than merely not yet gotten to.
+    /// - The store is then reconciled against the catalog the same way `check_data_integrity`
+    ///   does (`utils::reconcile_sorted_hashes`), and any chunk present on disk with no
+    ///   referencing row at all — e.g. written just before a crash lost the db row pointing at it
+    ///   — is a *candidate* orphan. `import_file` writes a chunk's blob to the store well before
+    ///   its db transaction commits, so a chunk from a still-in-flight import can look exactly
+    ///   like this; snapshotting the store and db hash lists up front isn't enough to rule that
+    ///   out; since the gap between the store write and the commit can be arbitrarily long (an
+    ///   `ffmpeg` probe runs in between). So candidates aren't deleted immediately: this pass waits
+    ///   `RepoOptions::sweep_grace_period` and re-checks each candidate against the db
+    ///   (`Database::chunks_exist`) before deleting it, giving any import that was in flight when
+    ///   the snapshots were taken time to commit and reclaim its chunk from the candidate set.
+    ///
+    /// Exposed as a plain on-demand async method, the same way `check_data_integrity`/`repair`
+    /// are: call this directly from the CLI's `sweep` subcommand, a cron job, or an embedding
+    /// application's own timer for full control over when it runs. For callers who'd rather not
+    /// wire up their own scheduling, `reaper::spawn_auto_sweep` runs this on a timer (or an
+    /// explicit ping) in a background task instead.
+    ///
+    /// # Errors
+    /// - `ErrorKind::DB` if reading or deleting items fails.
+    /// - `ErrorKind::IO` if the store or a thumbnail file cannot be read or deleted.
+    pub async fn sweep(&mut self) -> Result<SweepSummary> {
+        let mut summary = SweepSummary::default();
+
+        let cutoff = now_unix_timestamp() - self.incomplete_ttl.as_secs() as i64;
+        let incomplete_items = self
+            .db
+            .get_items(&Filter::with_tag("meta:Incomplete"))
+            .await?;
+        for item in incomplete_items {
+            if item.imported_at > cutoff {
+                continue;
+            }
+            let reclaimed_chunks = self.delete_item(&item.hash).await?;
+            summary.reclaimed_chunks.extend(reclaimed_chunks);
+            summary.expired_items.push(item.hash);
+        }
+
+        let mut store_chunk_hashes = self.store.list()?;
+        store_chunk_hashes.sort();
+        let db_chunk_hashes = self.db.get_all_chunk_hashes().await?;
+        let (_, orphan_candidates) =
+            utils::reconcile_sorted_hashes(&db_chunk_hashes, &store_chunk_hashes);
+
+        if !orphan_candidates.is_empty() {
+            tokio::time::sleep(self.sweep_grace_period).await;
+            let now_referenced: HashSet<String> =
+                self.db.chunks_exist(&orphan_candidates).await?.into_iter().collect();
+            for chunk_hash in orphan_candidates {
+                if now_referenced.contains(&chunk_hash) {
+                    continue;
                 }
-                let ext = path
-                    .extension()
-                    .expect("Store item must have an extension.")
-                    .to_string_lossy()
-                    .to_string();
-                found_files.push((expected_hash, ext));
+                self.store.delete(&chunk_hash)?;
+                summary.orphaned_chunks.push(chunk_hash);
             }
         }
-        Ok(())
+
+        Ok(summary)
     }
 
     fn hash<T>(path: T) -> Result<String>
@@ -429,419 +1136,405 @@ impl Repo {
         let hash = hasher.finalize();
         Ok(hex::encode(hash))
     }
+
+    fn hash_bytes(data: &[u8]) -> String {
+        let mut hasher = Sha224::new();
+        hasher.update(data);
+        hex::encode(hasher.finalize())
+    }
+
+    /// Imports a remote item through `fetcher`, picking a rendition with `filter` and seeding
+    /// `title`/duration from the probed `fetch::RemoteMetadata` before the downloaded bytes go
+    /// through the same hash/chunk/import pipeline `import_file` uses for local files.
+    ///
+    /// The downloaded bytes are staged as a temp file first, since thumbnailing and technical
+    /// metadata probing both open the file themselves via `ffmpeg`, the same way they would for a
+    /// freshly-imported local file.
+    ///
+    /// # Errors
+    /// - `ErrorKind::Unsupported` if `fetcher` reports no formats for `reference`, or none of them
+    ///   satisfy `filter`.
+    /// - `ErrorKind::Duplicate` if an item with the downloaded content's hash already exists.
+    /// - Otherwise, whatever `fetcher` itself returns, or an `ErrorKind::IO` staging the download.
+    pub async fn import_url(
+        &mut self,
+        reference: &str,
+        fetcher: &impl Fetcher,
+        filter: &StreamFilter,
+    ) -> Result<()> {
+        let remote_metadata = fetcher.probe(reference).await?;
+        let Some(format) = filter.select(&remote_metadata.formats) else {
+            return Err(Error::with_args(
+                ErrorKind::Unsupported,
+                "unsupported",
+                vec![("path", reference.to_string())],
+            ));
+        };
+        let data = fetcher.fetch(reference, format).await?;
+
+        let hash = Repo::hash_bytes(&data);
+        let ext = format.container.clone();
+        let temp_path = std::env::temp_dir().join(format!("vorg-import-{hash}.{ext}"));
+        fs::write(&temp_path, &data)?;
+
+        let chunks = chunking::chunk_bytes(&data);
+        let mut chunk_sizes = Vec::with_capacity(chunks.len());
+        for chunk in &chunks {
+            if !self.db.chunk_exists(&chunk.hash).await? {
+                self.store.put(&chunk.hash, &chunk.data)?;
+            }
+            chunk_sizes.push((chunk.hash.clone(), chunk.data.len() as i64));
+        }
+
+        // `remote_metadata.duration` only fills in for a file ffmpeg itself can't find a
+        // duration in, e.g. a container it doesn't recognize.
+        let mut probed_metadata = metadata::probe(MediaKind::Video, &temp_path);
+        if probed_metadata.duration.is_none() {
+            probed_metadata.duration = remote_metadata.duration;
+        }
+
+        let tags = vec![String::from("meta:Incomplete")];
+        let result = self
+            .db
+            .import_file_chunked(
+                &remote_metadata.title,
+                &hash,
+                &ext,
+                MediaKind::Video,
+                &tags,
+                &probed_metadata,
+                &chunk_sizes,
+            )
+            .await;
+
+        if result.is_ok() {
+            let thumbnail_result = thumbnail::generate_video(
+                &temp_path,
+                &hash,
+                &self.thumbnail_path,
+                self.thumbnail_size,
+            );
+            if let Err(error) = thumbnail_result {
+                eprintln!("Error encountered: {error}. Ignoring.");
+            }
+
+            // Same best-effort scrub-preview storyboard `import_file` generates for every video;
+            // `import_url` always imports a video, so there's no `MediaKind` check to guard it.
+            let storyboard_result =
+                storyboard::generate(&temp_path, self.store.as_ref(), &self.storyboard_options);
+            if let Err(error) = storyboard_result {
+                eprintln!("Error encountered: {error}. Ignoring.");
+            }
+        }
+
+        fs::remove_file(&temp_path)?;
+        result
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    struct TestFixture<T>
-    where
-        T: AsRef<Path>,
-    {
-        path: T,
+    use test_context::{test_context, AsyncTestContext};
+    use uuid::Uuid;
+
+    /// The smallest possible valid PNG: a single opaque pixel. Real enough for libmagic to sniff
+    /// as `image/png` and for `thumbnail::generate_image` to decode, without needing the video
+    /// fixtures (ffmpeg, sample `.mp4`s) these tests used to depend on before being commented out.
+    const TINY_PNG: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x90,
+        0x77, 0x53, 0xde, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0xa8,
+        0x10, 0x39, 0x01, 0x00, 0x02, 0x5c, 0x01, 0x55, 0x99, 0xf2, 0x64, 0x7b, 0x00, 0x00, 0x00,
+        0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    struct TempFolder {
+        pub path: PathBuf,
     }
 
-    impl<T> TestFixture<T>
-    where
-        T: AsRef<Path>,
-    {
-        fn new(path: T) -> Self {
-            TestFixture { path }
+    #[async_trait::async_trait]
+    impl AsyncTestContext for TempFolder {
+        async fn setup() -> TempFolder {
+            let uuid = Uuid::new_v4();
+            let path = std::env::temp_dir()
+                .join(format!("vorg-lib-test-{}", uuid.hyphenated()));
+            fs::create_dir_all(&path).expect("Failed to create temp dir for testing.");
+            TempFolder { path }
+        }
+
+        async fn teardown(self) {
+            let _ = fs::remove_dir_all(&self.path);
         }
     }
 
-    impl<T> Drop for TestFixture<T>
-    where
-        T: AsRef<Path>,
-    {
-        fn drop(&mut self) {
-            let path = self.path.as_ref();
-            if path.is_dir() {
-                fs::remove_dir_all(path).expect("Failed to teardown temp test directory.");
-            } else {
-                fs::remove_file(path).expect("Failed to teardown test file.");
-            }
+    impl TempFolder {
+        /// Opens a hermetic `Repo` rooted at this temp dir, with an in-memory chunk store so
+        /// tests don't depend on `LocalFsStore`'s real file layout.
+        async fn repo(&self) -> Repo {
+            self.repo_with_options(RepoOptions::default()).await
+        }
+
+        async fn repo_with_options(&self, options: RepoOptions) -> Repo {
+            Repo::new_with_options(self.path.join("repo"), options)
+                .await
+                .expect("Failed to open test repo.")
+                .with_store(MemoryStore::new())
+        }
+
+        /// Writes `TINY_PNG` to a fresh file under this temp dir and returns its path, ready to
+        /// hand to `Repo::import` (which moves/removes the file as part of importing it).
+        fn stage_png(&self, name: &str) -> PathBuf {
+            let path = self.path.join(name);
+            fs::write(&path, TINY_PNG).expect("Failed to stage test file.");
+            path
         }
     }
 
-    //     #[test]
-    //     async fn test_create_repo() {
-    //         let repo_path = "temp/create_repo";
-    //         let _f = TestFixture::new(repo_path);
-
-    //         let result = Repo::new(repo_path).await;
-    //         assert!(result.is_ok());
-
-    //         // Make sure store exists
-    //         let repo_path = Path::new(repo_path);
-    //         let store_path = repo_path.join("store");
-    //         assert!(store_path.is_dir());
-
-    //         // Make sure thumbnail path exists
-    //         let thumbnail_path = repo_path.join("thumbnail");
-    //         assert!(thumbnail_path.is_dir());
-
-    //         // Make sure database exists and passes validate db
-    //         let db_path = repo_path.join("vorg.db");
-    //         assert!(db_path.is_file());
-    //         let test_db = DB::new(db_path).await;
-    //         assert!(test_db.is_ok());
-    //     }
-
-    //     #[test]
-    //     async fn test_create_repo_failed() {
-    //         let repo_path = "temp/create_repo_failed";
-    //         let _f = TestFixture::new(repo_path);
-
-    //         fs::File::create(repo_path).unwrap();
-
-    //         let result = Repo::new(repo_path).await;
-    //         assert!(result.is_err());
-    //         assert_eq!(
-    //             result.unwrap_err().to_string(),
-    //             "The selected path is not a folder."
-    //         );
-    //     }
-
-    //     #[test]
-    //     async fn test_validate_repo_valid() {
-    //         let repo_path = "temp/create_validate_repo_valid";
-    //         let _f = TestFixture::new(repo_path);
-
-    //         // Create valid repo
-    //         // TODO: do not depend on Repo::new
-    //         {
-    //             let result = Repo::new(repo_path).await;
-    //             assert!(result.is_ok());
-    //         }
-
-    //         // Validate
-    //         let result = Repo::new(repo_path).await;
-    //         assert!(result.is_ok());
-    //     }
-
-    //     #[test]
-    //     async fn test_validate_repo_invalid1() {
-    //         let repo_path = "resources/repo/invalid-db";
-
-    //         let result = Repo::new(repo_path).await;
-    //         assert!(result.is_err());
-    //         assert_eq!(result.unwrap_err().to_string(), "file is not a database");
-    //     }
-
-    //     #[test]
-    //     async fn test_validate_repo_invalid2() {
-    //         let repo_path = "resources/repo/invalid-store-not-dir";
-
-    //         let result = Repo::new(repo_path).await;
-    //         assert!(result.is_err());
-    //         assert_eq!(
-    //             result.unwrap_err().to_string(),
-    //             "Store does not exist or is not a directory."
-    //         );
-    //     }
-
-    //     #[test]
-    //     async fn test_validate_repo_invalid3() {
-    //         let repo_path = "resources/repo/invalid-thumbnail-not-dir";
-
-    //         let result = Repo::new(repo_path).await;
-    //         assert!(result.is_err());
-    //         assert_eq!(
-    //             result.unwrap_err().to_string(),
-    //             "Thumbnail store does not exist or is not a directory."
-    //         );
-    //     }
-
-    //     #[test]
-    //     async fn test_import_file() {
-    //         let repo_path = PathBuf::from("temp/repo_import_file");
-    //         let _f = TestFixture::new(&repo_path);
-    //         let video_path = PathBuf::from("temp/repo_import_file_videos");
-    //         let _f2 = TestFixture::new(&video_path);
-    //         fs::create_dir(&video_path).unwrap();
-
-    //         // Make copy before importing
-    //         let file_to_import = video_path.join("black.mp4");
-    //         fs::copy("resources/video/black.mp4", &file_to_import).unwrap();
-
-    //         // TODO: do not depend on Repo::new
-    //         let original_file_size = file_to_import.metadata().unwrap().len();
-    //         let mut repo = Repo::new(&repo_path).await.unwrap();
-    //         let result = repo.import(&file_to_import).await;
-    //         assert!(result.is_ok());
-
-    //         // Verify store
-    //         let expected_store_path = repo_path
-    //             .join("store")
-    //             .join("4e")
-    //             .join("ffadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633.mp4");
-    //         assert!(expected_store_path.exists());
-    //         assert_eq!(
-    //             original_file_size,
-    //             expected_store_path.metadata().unwrap().len()
-    //         );
-    //         assert!(!file_to_import.exists());
-
-    //         // Verify DB
-    //         // let mut connection = SqliteConnection::connect(repo_path.()()("vorg.db"n.to_string().as_str()).await.unwrap();
-    //         // let query = "
-    //         // SELECT hash FROM items
-    //         // ";
-    //         // let results = sqlx::query(query).fetch_all(&mut connection).await.unwrap();
-    //         // assert_eq!(results.len(), 1);
-    //         // assert_eq!(
-    //         //     statement.read::<String, _>(0).unwrap(),
-    //         //     "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633"
-    //         // );
-    //         // assert_eq!(statement.read::<String, _>(1).unwrap(), "black");
-    //         // assert_eq!(statement.read::<String, _>(2).unwrap(), "mp4");
-    //         // assert_eq!(statement.read::<i64, _>(3).unwrap(), 0);
-
-    //         // let result = statement.next();
-    //         // assert!(result.is_ok());
-    //         // assert_eq!(result.unwrap(), sqlite::State::Done);
-
-    //         // TODO: verify thumbnail
-
-    //         // Test duplicate import
-    //         fs::copy("resources/video/black.mp4", &file_to_import).unwrap();
-    //         let result = repo.import(&file_to_import).await;
-    //         assert!(result.is_err());
-    //         assert_eq!(
-    //             result.unwrap_err().to_string(),
-    //             "The item to import already exists in the database."
-    //         );
-
-    //         // TODO: Give get_files an independent test
-    //         let result = repo.get_files().await;
-    //         assert!(result.is_ok());
-    //         assert_eq!(result.unwrap().len(), 1);
-    //     }
-
-    //     #[test]
-    //     async fn test_import_file_unsupported() {
-    //         let repo_path = PathBuf::from("temp/repo_import_file_unsupported");
-    //         let _f = TestFixture::new(&repo_path);
-
-    //         let file_to_import = PathBuf::from("resources/video/fake-video.txt");
-    //         let mut repo = Repo::new(&repo_path).await.unwrap();
-    //         let result = repo.import(&file_to_import).await;
-    //         assert!(result.is_err());
-    //         assert_eq!(
-    //             result.unwrap_err().to_string(),
-    //             "File with type inode/x-empty is not supported."
-    //         );
-    //         assert!(file_to_import.exists());
-    //     }
-
-    //     #[test]
-    //     async fn test_import_file_nonexistent() {
-    //         let repo_path = PathBuf::from("temp/repo_import_file_nonexistent");
-    //         let _f = TestFixture::new(&repo_path);
-
-    //         let file_to_import = PathBuf::from("resources/video/no.mp4");
-    //         let mut repo = Repo::new(&repo_path).await.unwrap();
-    //         let result = repo.import(&file_to_import).await;
-    //         assert!(result.is_err());
-    //         assert_eq!(
-    //             result.unwrap_err().to_string(),
-    //             "The selected file does not exist."
-    //         );
-    //     }
-
-    //     #[test]
-    //     async fn test_import_file_subfolder_exists() {
-    //         let repo_path = PathBuf::from("temp/repo_import_file_subfolder_exists");
-    //         let _f = TestFixture::new(&repo_path);
-    //         let video_path = PathBuf::from("temp/repo_import_file_subfolder_exists_video");
-    //         let _f2 = TestFixture::new(&video_path);
-    //         fs::create_dir(&video_path).unwrap();
-
-    //         // Make copy before importing
-    //         let file_to_import = video_path.join("black.mp4");
-    //         fs::copy("resources/video/black.mp4", &file_to_import).unwrap();
-    //         let original_file_size = file_to_import.metadata().unwrap().len();
-    //         let mut repo = Repo::new(&repo_path).await.unwrap();
-
-    //         // Create store subfolder
-    //         fs::create_dir(repo_path.join("store").join("4e")).unwrap();
-    //         fs::create_dir(repo_path.join("thumbnail").join("4e")).unwrap();
-
-    //         // Import
-    //         let result = repo.import(&file_to_import).await;
-    //         assert!(result.is_ok());
-
-    //         // Verify store
-    //         let expected_store_path = repo_path
-    //             .join("store")
-    //             .join("4e")
-    //             .join("ffadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633.mp4");
-    //         assert!(expected_store_path.exists());
-    //         assert_eq!(
-    //             original_file_size,
-    //             expected_store_path.metadata().unwrap().len()
-    //         );
-    //         assert!(!file_to_import.exists());
-
-    //         // TODO: verify thumbnail
-    //     }
-
-    //     #[test]
-    //     async fn test_import_file_store_corrupted() {
-    //         let repo_path = PathBuf::from("temp/repo_import_file_corrupted");
-    //         let _f = TestFixture::new(&repo_path);
-    //         let video_path = PathBuf::from("temp/repo_import_file_corrupted_video");
-    //         let _f2 = TestFixture::new(&video_path);
-    //         fs::create_dir(&video_path).unwrap();
-
-    //         // Make copy before importing
-    //         let file_to_import = video_path.join("black.mp4");
-    //         fs::copy("resources/video/black.mp4", &file_to_import).unwrap();
-    //         let mut repo = Repo::new(&repo_path).await.unwrap();
-
-    //         // Create store subfolder
-    //         fs::File::create(repo_path.join("store").join("4e")).unwrap();
-
-    //         // Import
-    //         let result = repo.import(&file_to_import).await;
-    //         assert!(result.is_err());
-    //         assert_eq!(
-    //             result.unwrap_err().to_string(),
-    //             "Repo store is corrupted with regular files directly within."
-    //         );
-    //     }
-
-    //     #[test]
-    //     async fn test_import_folder() {
-    //         let repo_path = PathBuf::from("temp/repo_import_dir");
-    //         let _f = TestFixture::new(&repo_path);
-    //         let video_path = PathBuf::from("temp/repo_import_dir_videos");
-    //         let _f2 = TestFixture::new(&video_path);
-
-    //         // Prepare video dir
-    //         fs::create_dir_all(video_path.join("nested").join("another")).unwrap();
-    //         fs::copy(
-    //             "resources/video/black.mp4",
-    //             "temp/repo_import_dir_videos/random title 1.mp4",
-    //         )
-    //         .unwrap();
-    //         fs::copy(
-    //             "resources/video/gray.mp4",
-    //             "temp/repo_import_dir_videos/nested/random title 2.mp4",
-    //         )
-    //         .unwrap();
-    //         fs::copy(
-    //             "resources/video/large.mp4",
-    //             "temp/repo_import_dir_videos/nested/another/random title 3.mp4",
-    //         )
-    //         .unwrap();
-    //         fs::copy(
-    //             "resources/video/white.mp4",
-    //             "temp/repo_import_dir_videos/random title 4.mp4",
-    //         )
-    //         .unwrap();
-    //         fs::copy(
-    //             "resources/video/fake-video.txt",
-    //             "temp/repo_import_dir_videos/fake video.txt",
-    //         )
-    //         .unwrap();
-
-    //         // Prepare repo and import
-    //         let mut repo = Repo::new(&repo_path).await.unwrap();
-    //         let result = repo.import(&video_path).await;
-    //         assert!(result.is_ok());
-
-    //         // Verify non-video files are not touched
-    //         assert!(PathBuf::from("temp/repo_import_dir_videos/fake video.txt").exists());
-
-    //         // Verify
-    //         let hashes = [
-    //             "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
-    //             "50a04dc1cbd3d8edd5ad7acbcaad95362fe1c47c212f7b6b2b66d8bc",
-    //             "effaa79355fe625a1df6e916b1c30a5f68ae76687dbd954d759353d6",
-    //             "f9d939a70a8fbea1b6bde16c41fcbc1ce5ebe8002c7ccfaf791b891d",
-    //         ];
-    //         let mut titles = HashMap::new();
-    //         titles.insert(
-    //             "4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633",
-    //             "random title 1",
-    //         );
-    //         titles.insert(
-    //             "50a04dc1cbd3d8edd5ad7acbcaad95362fe1c47c212f7b6b2b66d8bc",
-    //             "random title 2",
-    //         );
-    //         titles.insert(
-    //             "effaa79355fe625a1df6e916b1c30a5f68ae76687dbd954d759353d6",
-    //             "random title 3",
-    //         );
-    //         titles.insert(
-    //             "f9d939a70a8fbea1b6bde16c41fcbc1ce5ebe8002c7ccfaf791b891d",
-    //             "random title 4",
-    //         );
-
-    //         // Verify store
-    //         for hash in hashes {
-    //             let store_path = repo_path
-    //                 .join("store")
-    //                 .join(&hash[0..2])
-    //                 .join(format!("{}.mp4", &hash[2..]));
-    //             assert!(store_path.exists());
-    //         }
-
-    //         // Verify db
-    //         // let connection = sqlite::open("temp/repo_import_dir/vorg.db").unwrap();
-    //         // let query = "
-    //         //     SELECT hash,title,ext,studio_id FROM items ORDER BY hash
-    //         // ";
-    //         // let mut statement = connection.prepare(query).unwrap();
-    //         // let mut count = 0;
-    //         // while let Ok(sqlite::State::Row) = statement.next() {
-    //         //     assert_eq!(statement.read::<String, _>(0).unwrap(), hashes[count]);
-    //         //     assert_eq!(
-    //         //         statement.read::<String, _>(1).unwrap(),
-    //         //         *titles.get(&hashes[count]).unwrap()
-    //         //     );
-    //         //     assert_eq!(statement.read::<String, _>(2).unwrap(), "mp4");
-    //         //     assert_eq!(statement.read::<i64, _>(3).unwrap(), 0);
-    //         //     count += 1;
-    //         // }
-    //         // assert_eq!(count, 4);
-
-    //         // TODO: Verify thumbnail
-    //     }
-
-    //     #[test]
-    //     async fn test_check_data_integrity() {
-    //         let mut repo = Repo::new("resources/repo/db-not-store").await.unwrap();
-    //         let result = repo.check_data_integrity().await.unwrap();
-    //         assert_eq!(result, "store: file not found in store: 4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633
-    // store: file not found in store: effaa79355fe625a1df6e916b1c30a5f68ae76687dbd954d759353d6
-    // ");
-
-    //         let mut repo = Repo::new("resources/repo/store-not-db").await.unwrap();
-    //         let result = repo.check_data_integrity().await.unwrap();
-    //         assert_eq!(result, "store: redundant file in store: 4effadeed3957d9dab1a645b9a7d01c18380d54e71d51148fdf84633
-    // store: redundant file in store: effaa79355fe625a1df6e916b1c30a5f68ae76687dbd954d759353d6
-    // ");
-
-    //         let mut repo = Repo::new("resources/repo/wrong-hash-ext").await.unwrap();
-    //         let result = repo.check_data_integrity().await.unwrap();
-    //         assert_eq!(result, "ext: different extensions: avi in db but mp4 in store
-    // hash: Expected 50a04dc1cbd3d8edd5ad7acbcaad95362fe1c47c212f7b6b2b66d8bd, but real hash is 50a04dc1cbd3d8edd5ad7acbcaad95362fe1c47c212f7b6b2b66d8bc
-    // ");
-    //     }
-
-    //     #[test]
-    //     async fn test_debug_fmt() {
-    //         let repo_path = "temp/repo_debug_fmt";
-    //         let _f = TestFixture::new(repo_path);
-
-    //         let repo = Repo::new(repo_path).await.unwrap();
-    //         let debug_fmt = format!("{repo:?}");
-    //         assert!(
-    //             debug_fmt.starts_with("Repo { db: Placeholder debug implementation for vorgrs::db::DB")
-    //         );
-    //     }
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn new_creates_store_and_thumbnail_dirs_and_a_database(ctx: &TempFolder) {
+        let repo_path = ctx.path.join("repo");
+
+        Repo::new(&repo_path).await.expect("Failed to create repo.");
+
+        assert!(repo_path.join("store").is_dir());
+        assert!(repo_path.join("thumbnail").is_dir());
+        assert!(repo_path.join("vorg.db").is_file());
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn new_reopens_an_existing_repo(ctx: &TempFolder) {
+        let repo_path = ctx.path.join("repo");
+
+        Repo::new(&repo_path).await.expect("Failed to create repo.");
+        Repo::new(&repo_path).await.expect("Failed to reopen existing repo.");
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn new_rejects_a_path_that_is_a_plain_file(ctx: &TempFolder) {
+        let repo_path = ctx.path.join("not-a-folder");
+        fs::write(&repo_path, b"not a repo").unwrap();
+
+        let result = Repo::new(&repo_path).await;
+
+        assert!(matches!(result, Err(e) if e.kind == ErrorKind::IO));
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn import_writes_a_tagged_item_visible_via_get_files(ctx: &TempFolder) {
+        let mut repo = ctx.repo().await;
+        let file_to_import = ctx.stage_png("photo.png");
+
+        let outcome = repo.import(&file_to_import).await.expect("Import should succeed.");
+
+        assert_eq!(outcome.chunks_written, 1);
+        assert!(!file_to_import.exists(), "import should move the original file away");
+
+        let items = repo.get_files(&Filter::new()).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].ext, "png");
+        assert_eq!(items[0].media_kind, MediaKind::Image);
+        assert!(items[0].tags.iter().any(|tag| tag == "meta:Incomplete"));
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn import_rejects_a_duplicate(ctx: &TempFolder) {
+        let mut repo = ctx.repo().await;
+        repo.import(&ctx.stage_png("first.png")).await.expect("First import should succeed.");
+
+        let result = repo.import(&ctx.stage_png("second.png")).await;
+
+        assert!(matches!(result, Err(e) if e.kind == ErrorKind::Duplicate));
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn import_rejects_an_unsupported_file_type(ctx: &TempFolder) {
+        let mut repo = ctx.repo().await;
+        let file_to_import = ctx.path.join("notes.txt");
+        fs::write(&file_to_import, b"plain text, not a supported media type").unwrap();
+
+        let result = repo.import(&file_to_import).await;
+
+        assert!(matches!(result, Err(e) if e.kind == ErrorKind::Unsupported));
+        assert!(file_to_import.exists(), "a rejected file should be left in place");
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn check_data_integrity_and_repair_handle_a_missing_thumbnail(ctx: &TempFolder) {
+        let mut repo = ctx.repo().await;
+        repo.import(&ctx.stage_png("photo.png")).await.expect("Import should succeed.");
+        let item = repo.get_files(&Filter::new()).await.unwrap().remove(0);
+        let thumbnail_path = thumbnail::path_for(&repo.thumbnail_path, &item.hash);
+        fs::remove_file(&thumbnail_path).expect("Failed to remove generated thumbnail.");
+
+        let findings = repo.check_data_integrity(|_, _| {}).await.unwrap();
+        assert!(
+            findings
+                .iter()
+                .any(|finding| finding.category == report::FindingCategory::ThumbnailMissing
+                    && finding.path == item.hash)
+        );
+
+        // A missing thumbnail cannot be regenerated without the original file, so `repair` can
+        // only record it for manual review, not fix it.
+        let summary = repo.repair().await.unwrap();
+        assert!(summary.unresolved.iter().any(|line| line.contains(&item.hash)));
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn repair_quarantines_a_chunk_the_db_no_longer_references(ctx: &TempFolder) {
+        let mut repo = ctx.repo().await;
+        repo.import(&ctx.stage_png("photo.png")).await.expect("Import should succeed.");
+        let stray_hash = Repo::hash_bytes(b"nobody references this chunk");
+        repo.store.put(&stray_hash, b"nobody references this chunk").unwrap();
+
+        let summary = repo.repair().await.unwrap();
+
+        assert!(summary.passes.iter().flatten().any(|action| action.contains(&stray_hash)));
+        assert!(!repo.store.list().unwrap().contains(&stray_hash));
+        assert!(repo.path.join("quarantine").join(&stray_hash).is_file());
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn repair_reports_a_permanent_finding_only_once_across_passes(ctx: &TempFolder) {
+        let mut repo = ctx.repo().await;
+        repo.import(&ctx.stage_png("photo.png")).await.expect("Import should succeed.");
+        let item = repo.get_files(&Filter::new()).await.unwrap().remove(0);
+        // The db still references `item.hash`, but its chunk is gone from the store and nothing
+        // can regenerate it, so this finding (`ChunkMissing`) is permanent and recurs every pass.
+        repo.store.delete(&item.hash).unwrap();
+        // A fixable finding alongside it (`ChunkUnexpected`), resolved in the first pass, so the
+        // second pass sees a shrunk but non-empty finding set made up solely of the permanent one.
+        let stray_hash = Repo::hash_bytes(b"nobody references this chunk");
+        repo.store.put(&stray_hash, b"nobody references this chunk").unwrap();
+
+        let summary = repo.repair().await.unwrap();
+
+        assert!(summary.passes.len() >= 2, "the permanent finding should force a second pass");
+        assert_eq!(
+            summary.unresolved.iter().filter(|line| line.contains(&item.hash)).count(),
+            1,
+            "a permanent finding must not be re-reported every pass it survives"
+        );
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn repair_reports_a_hash_mismatch_only_once_after_it_becomes_chunk_missing(
+        ctx: &TempFolder,
+    ) {
+        let mut repo = ctx.repo().await;
+        repo.import(&ctx.stage_png("photo.png")).await.expect("Import should succeed.");
+        let item = repo.get_files(&Filter::new()).await.unwrap().remove(0);
+        // Overwrite the chunk's content in place, so it still exists under `item.hash` but no
+        // longer hashes to it: the first pass reports `ChunkHashMismatch` and quarantines it,
+        // which deletes it from the store outright. The db still references `item.hash`, which
+        // nothing can regenerate, so the second pass reports the very same hash again, this time
+        // as `ChunkMissing`.
+        repo.store.put(&item.hash, b"corrupted content").unwrap();
+
+        let summary = repo.repair().await.unwrap();
+
+        assert!(summary.passes.len() >= 2, "the chunk becoming missing should force a second pass");
+        assert!(repo.path.join("corrupted").join(&item.hash).is_file());
+        assert_eq!(
+            summary.unresolved.iter().filter(|line| line.contains(&item.hash)).count(),
+            1,
+            "a hash mismatch that becomes a chunk-missing finding must only be reported once"
+        );
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn delete_item_removes_its_chunk_and_thumbnail(ctx: &TempFolder) {
+        let mut repo = ctx.repo().await;
+        repo.import(&ctx.stage_png("photo.png")).await.expect("Import should succeed.");
+        let item = repo.get_files(&Filter::new()).await.unwrap().remove(0);
+        let thumbnail_path = thumbnail::path_for(&repo.thumbnail_path, &item.hash);
+        assert!(thumbnail_path.is_file());
+
+        let reclaimed_chunks = repo.delete_item(&item.hash).await.unwrap();
+
+        assert_eq!(reclaimed_chunks.len(), 1);
+        assert!(repo.store.list().unwrap().is_empty());
+        assert!(!thumbnail_path.is_file());
+        assert!(repo.get_files(&Filter::new()).await.unwrap().is_empty());
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn sweep_reclaims_an_expired_incomplete_item(ctx: &TempFolder) {
+        // `incomplete_ttl: 0` means the item this test imports is already past its TTL the
+        // instant it's tagged `meta:Incomplete`, so `sweep` reclaims it deterministically rather
+        // than depending on real wall-clock time passing.
+        let options =
+            RepoOptions { incomplete_ttl: Duration::from_secs(0), ..RepoOptions::default() };
+        let mut repo = ctx.repo_with_options(options).await;
+        repo.import(&ctx.stage_png("photo.png")).await.expect("Import should succeed.");
+        let item = repo.get_files(&Filter::new()).await.unwrap().remove(0);
+
+        let summary = repo.sweep().await.unwrap();
+
+        assert_eq!(summary.expired_items, vec![item.hash]);
+        assert_eq!(summary.reclaimed_chunks.len(), 1);
+        assert!(repo.get_files(&Filter::new()).await.unwrap().is_empty());
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn sweep_reclaims_an_orphaned_chunk_after_the_grace_period(ctx: &TempFolder) {
+        let options =
+            RepoOptions { sweep_grace_period: Duration::from_millis(1), ..RepoOptions::default() };
+        let mut repo = ctx.repo_with_options(options).await;
+        let orphan_hash = Repo::hash_bytes(b"written to the store, never committed to the db");
+        repo.store.put(&orphan_hash, b"written to the store, never committed to the db").unwrap();
+
+        let summary = repo.sweep().await.unwrap();
+
+        assert_eq!(summary.orphaned_chunks, vec![orphan_hash.clone()]);
+        assert!(!repo.store.list().unwrap().contains(&orphan_hash));
+    }
+
+    #[test_context(TempFolder)]
+    #[tokio::test]
+    async fn import_archive_restores_a_usable_db_connection(ctx: &TempFolder) {
+        let mut source = Repo::new(ctx.path.join("source"))
+            .await
+            .expect("Failed to open source test repo.")
+            .with_store(MemoryStore::new());
+        source.import(&ctx.stage_png("photo.png")).await.expect("Import should succeed.");
+        let archive_path = ctx.path.join("export.vorgarchive");
+        source.export(&archive_path).await.expect("Export should succeed.");
+
+        let mut target = Repo::new(ctx.path.join("target"))
+            .await
+            .expect("Failed to open target test repo.")
+            .with_store(MemoryStore::new());
+        let summary = target
+            .import_archive(&archive_path, false)
+            .await
+            .expect("Importing the archive should succeed.");
+        assert!(summary.db_restored);
+
+        // The restored db file was written directly to `vorg.db`'s path while `target.db` still
+        // held a connection opened before the restore; if that connection wasn't reopened against
+        // the new file, these would either see a stale (empty) catalog or fail outright.
+        let items = target.get_files(&Filter::new()).await.expect("query_items should still work.");
+        assert_eq!(items.len(), 1);
+
+        target
+            .import(&ctx.stage_png("second.png"))
+            .await
+            .expect("A further import on the same Repo should still work.");
+        assert_eq!(target.get_files(&Filter::new()).await.unwrap().len(), 2);
+    }
 }
@@ -1,17 +1,10 @@
 use std::{env, path::Path};
-use vorgrs::{Error, ErrorKind, Repo, Result};
+use vorgrs::{parse_query, to_json, to_text, Error, ErrorKind, Repo, Result};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
-    let wrong_arg_error = Error {
-        msg: String::from(
-            "Usage:
-    vorgrs import [vorg repo path] [file or folder to import]
-    vorgrs check [vorg repo path]",
-        ),
-        kind: ErrorKind::WrongArguments,
-    };
+    let wrong_arg_error = Error::new(ErrorKind::WrongArguments, "wrong-arguments");
 
     // TODO: rework arg parsing logic
     if args.len() < 2 {
@@ -26,19 +19,109 @@ async fn main() -> Result<()> {
         let mut repo = Repo::new(Path::new(&args[2])).await.unwrap();
 
         let path = Path::new(&args[3]);
-        repo.import(path).await.unwrap();
+        let outcome = repo.import(path).await.unwrap();
+        println!(
+            "Wrote {} chunk(s), deduplicated {} chunk(s) already in the store",
+            outcome.chunks_written, outcome.chunks_deduplicated
+        );
     } else if args[1] == "check" {
         if args.len() < 3 {
             return Err(wrong_arg_error);
         }
 
+        let format = match args.get(3).map(String::as_str) {
+            None => "text",
+            Some("--format") if args.len() > 4 => args[4].as_str(),
+            Some(_) => return Err(wrong_arg_error),
+        };
+        if format != "text" && format != "json" {
+            return Err(wrong_arg_error);
+        }
+
         let mut repo = Repo::new(Path::new(&args[2])).await.unwrap();
 
-        let result = repo
-            .check_data_integrity()
+        let findings = repo
+            .check_data_integrity(|hashed, total| eprintln!("Checked {hashed}/{total} chunks"))
             .await
             .expect("Error checking vorg repo.");
-        eprint!("{result}");
+        if format == "json" {
+            println!("{}", to_json(&findings));
+        } else {
+            eprint!("{}", to_text(&findings));
+        }
+    } else if args[1] == "repair" {
+        if args.len() < 3 {
+            return Err(wrong_arg_error);
+        }
+
+        let mut repo = Repo::new(Path::new(&args[2])).await.unwrap();
+
+        if args.len() > 3 && args[3] == "--dry-run" {
+            let diff = repo
+                .repair_dry_run()
+                .await
+                .expect("Error computing vorg repo repair plan.");
+            print!("{diff}");
+        } else {
+            let summary = repo.repair().await.expect("Error repairing vorg repo.");
+            for pass in &summary.passes {
+                for action in pass {
+                    println!("{action}");
+                }
+            }
+            for unresolved in &summary.unresolved {
+                eprintln!("unresolved: {unresolved}");
+            }
+        }
+    } else if args[1] == "export" {
+        if args.len() < 4 {
+            return Err(wrong_arg_error);
+        }
+
+        let mut repo = Repo::new(Path::new(&args[2])).await.unwrap();
+        repo.export(Path::new(&args[3]))
+            .await
+            .expect("Error exporting vorg repo.");
+    } else if args[1] == "import-archive" {
+        if args.len() < 4 {
+            return Err(wrong_arg_error);
+        }
+
+        let mut repo = Repo::new(Path::new(&args[2])).await.unwrap();
+        let merge = args.len() > 4 && args[4] == "--merge";
+        let summary = repo
+            .import_archive(Path::new(&args[3]), merge)
+            .await
+            .expect("Error importing archive into vorg repo.");
+        println!(
+            "Restored {} chunk(s), {} thumbnail(s), db: {}",
+            summary.chunks_restored, summary.thumbnails_restored, summary.db_restored
+        );
+    } else if args[1] == "sweep" {
+        if args.len() < 3 {
+            return Err(wrong_arg_error);
+        }
+
+        let mut repo = Repo::new(Path::new(&args[2])).await.unwrap();
+        let summary = repo.sweep().await.expect("Error sweeping vorg repo.");
+        println!(
+            "Expired {} item(s), removed {} blob(s) ({} orphaned)",
+            summary.expired_items.len(),
+            summary.total_blobs_removed(),
+            summary.orphaned_chunks.len()
+        );
+    } else if args[1] == "query" {
+        if args.len() < 4 {
+            return Err(wrong_arg_error);
+        }
+
+        let mut repo = Repo::new(Path::new(&args[2])).await.unwrap();
+        let filter = parse_query(&args[3]).expect("Invalid query syntax.");
+        let items = repo.query_items(&filter).await.expect("Error querying vorg repo.");
+        for item in &items {
+            println!("{}  {}", item.hash, item.title);
+        }
+        println!("{} item(s) matched", items.len());
     } else {
         return Err(wrong_arg_error);
     }
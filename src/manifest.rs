@@ -0,0 +1,144 @@
+//! Sidecar import manifest, letting `Repo::import_dir` catalog a folder in one shot.
+//!
+//! Every imported file used to be seeded with its full path as a placeholder title and a single
+//! `"meta:Incomplete"` tag, leaving a large folder entirely unorganized after import. If the folder
+//! passed to `import_dir` contains a `.vorg-import` file, `Manifest::load` parses it and
+//! `Manifest::entry_for` looks up the title/tags to apply to a given file by its path relative to
+//! that folder; files the manifest doesn't mention keep the old placeholder behavior. Matching is
+//! by exact relative path rather than glob, to keep the format (and this module) simple.
+
+use crate::error::{Error, ErrorKind, Result};
+use std::{fs, path::Path};
+
+/// Name of the optional sidecar file `import_dir` looks for at the top of the folder being
+/// imported.
+pub const MANIFEST_FILE_NAME: &str = ".vorg-import";
+
+/// The title and tags a manifest line asks to be applied to a matched file.
+pub struct ManifestEntry {
+    pub title: String,
+    pub tags: Vec<String>,
+}
+
+/// A parsed `.vorg-import` manifest.
+///
+/// One entry per non-blank, non-comment (`#`) line, each of the form
+/// `relative/path | Title | tag-one, tag-two`. The tag list is optional; an entry with no tags
+/// still gets its title applied.
+pub struct Manifest {
+    entries: Vec<(String, ManifestEntry)>,
+}
+
+impl Manifest {
+    /// Loads `<dir>/.vorg-import`, or returns `Ok(None)` if `dir` has no manifest.
+    ///
+    /// # Errors
+    /// - `ErrorKind::IO` if the manifest exists but cannot be read.
+    /// - `ErrorKind::InvalidManifest` if a line is missing its `path | title` fields.
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let manifest_path = dir.join(MANIFEST_FILE_NAME);
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&manifest_path)?;
+        let mut entries = Vec::new();
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.splitn(3, '|').map(str::trim);
+            let (Some(path), Some(title)) = (fields.next(), fields.next()) else {
+                return Err(Error::with_args(
+                    ErrorKind::InvalidManifest,
+                    "manifest-invalid-line",
+                    vec![("line", (line_number + 1).to_string())],
+                ));
+            };
+            let tags = fields
+                .next()
+                .map(|tags| {
+                    tags.split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            entries.push((
+                path.to_string(),
+                ManifestEntry {
+                    title: title.to_string(),
+                    tags,
+                },
+            ));
+        }
+
+        Ok(Some(Manifest { entries }))
+    }
+
+    /// Looks up the manifest entry for `relative_path` (a file's path relative to the folder the
+    /// manifest was loaded from), or `None` if the manifest doesn't mention it.
+    pub fn entry_for(&self, relative_path: &Path) -> Option<&ManifestEntry> {
+        let relative_path = relative_path.to_string_lossy();
+        self.entries
+            .iter()
+            .find(|(path, _)| *path == relative_path)
+            .map(|(_, entry)| entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_none_when_no_manifest_present() {
+        let dir = std::env::temp_dir().join("vorg-manifest-test-missing");
+        assert!(Manifest::load(&dir).unwrap().is_none());
+    }
+
+    #[test]
+    fn parses_title_and_tags() {
+        let dir = std::env::temp_dir().join("vorg-manifest-test-parse");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(MANIFEST_FILE_NAME),
+            "# a comment\nvideos/a.mp4 | A Title | one, two\nvideos/b.mp4 | B Title\n",
+        )
+        .unwrap();
+
+        let manifest = Manifest::load(&dir).unwrap().expect("manifest should load");
+        let a = manifest
+            .entry_for(Path::new("videos/a.mp4"))
+            .expect("a.mp4 should be in the manifest");
+        assert_eq!(a.title, "A Title");
+        assert_eq!(a.tags, vec!["one", "two"]);
+
+        let b = manifest
+            .entry_for(Path::new("videos/b.mp4"))
+            .expect("b.mp4 should be in the manifest");
+        assert_eq!(b.title, "B Title");
+        assert!(b.tags.is_empty());
+
+        assert!(manifest.entry_for(Path::new("videos/unmentioned.mp4")).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_line_missing_title() {
+        let dir = std::env::temp_dir().join("vorg-manifest-test-invalid");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE_NAME), "videos/a.mp4\n").unwrap();
+
+        let result = Manifest::load(&dir);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidManifest);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
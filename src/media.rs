@@ -0,0 +1,122 @@
+//! Media-type registry used to decide how `Repo::import_file` handles a given MIME type.
+//!
+//! `SUPPORTED_MIMETYPES` used to be a bare `HashMap<&str, &str>` from MIME type to default
+//! extension, and `import_file` assumed every supported file was a video. `lookup` replaces that
+//! with a small per-type descriptor (`MediaType`) carrying both the default extension and the
+//! `MediaKind`, so the import path can dispatch to the right thumbnailing strategy (and persist
+//! the kind on the item) from the single `lookup` call instead of hardcoding video everywhere.
+
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+/// Broad category of an imported item, persisted on `items.media_kind` and surfaced on `Item` so
+/// callers of `get_files` can distinguish videos from images without re-deriving it from `ext`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Video,
+    Image,
+}
+
+impl MediaKind {
+    /// String stored in `items.media_kind`.
+    pub fn as_db_str(self) -> &'static str {
+        match self {
+            MediaKind::Video => "video",
+            MediaKind::Image => "image",
+        }
+    }
+
+    /// Parses a value previously produced by `as_db_str`.
+    pub fn from_db_str(value: &str) -> Option<Self> {
+        match value {
+            "video" => Some(MediaKind::Video),
+            "image" => Some(MediaKind::Image),
+            _ => None,
+        }
+    }
+}
+
+/// Everything `import_file` needs to know about a supported MIME type.
+pub struct MediaType {
+    pub kind: MediaKind,
+    pub default_extension: &'static str,
+}
+
+lazy_static! {
+    /// MIME types (as reported by libmagic) that vorg can import today, and how.
+    static ref SUPPORTED_MEDIA_TYPES: HashMap<&'static str, MediaType> = {
+        let mut supported = HashMap::new();
+        supported.insert(
+            "video/mp4",
+            MediaType {
+                kind: MediaKind::Video,
+                default_extension: "mp4",
+            },
+        );
+        supported.insert(
+            "image/png",
+            MediaType {
+                kind: MediaKind::Image,
+                default_extension: "png",
+            },
+        );
+        supported.insert(
+            "image/jpeg",
+            MediaType {
+                kind: MediaKind::Image,
+                default_extension: "jpg",
+            },
+        );
+        supported.insert(
+            "image/webp",
+            MediaType {
+                kind: MediaKind::Image,
+                default_extension: "webp",
+            },
+        );
+        supported.insert(
+            "image/gif",
+            MediaType {
+                kind: MediaKind::Image,
+                default_extension: "gif",
+            },
+        );
+        supported
+    };
+}
+
+/// Looks up how to import `mime_type`, or `None` if vorg does not (yet) support it.
+pub fn lookup(mime_type: &str) -> Option<&'static MediaType> {
+    SUPPORTED_MEDIA_TYPES.get(mime_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_known_video_type() {
+        let media_type = lookup("video/mp4").expect("video/mp4 should be supported");
+        assert_eq!(media_type.kind, MediaKind::Video);
+        assert_eq!(media_type.default_extension, "mp4");
+    }
+
+    #[test]
+    fn looks_up_known_image_type() {
+        let media_type = lookup("image/png").expect("image/png should be supported");
+        assert_eq!(media_type.kind, MediaKind::Image);
+        assert_eq!(media_type.default_extension, "png");
+    }
+
+    #[test]
+    fn unknown_mime_type_is_unsupported() {
+        assert!(lookup("application/octet-stream").is_none());
+    }
+
+    #[test]
+    fn media_kind_db_str_roundtrips() {
+        for kind in [MediaKind::Video, MediaKind::Image] {
+            assert_eq!(MediaKind::from_db_str(kind.as_db_str()), Some(kind));
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Technical media metadata probed from an imported file and persisted on `items`.
+//!
+//! `Repo::import_file` used to record only `title`, `ext`, `hash`, and `media_kind` for an item,
+//! leaving duration, resolution, frame rate, container, and codec information undiscoverable
+//! without re-opening the original file (which by then has already been deleted). `probe` reads
+//! that information with `ffmpeg-next` (videos) or the `image` crate (images) while the file is
+//! still around, so it can be stored alongside the item and later queried or displayed without
+//! decoding anything again.
+//!
+//! Probing is best-effort: a file that is too corrupt or unusual for `ffmpeg`/`image` to make
+//! sense of just yields a `MediaMetadata` of all `None`s rather than failing the import.
+//! `Repo::import_file` runs the probe concurrently with chunking the file.
+
+use crate::media::MediaKind;
+use ffmpeg_next as ffmpeg;
+use std::path::Path;
+
+/// Technical metadata describing an imported file's content, independent of the `Item` fields
+/// (`title`, `ext`, `hash`, `media_kind`) that describe how vorg itself tracks it.
+///
+/// Every field is optional: images don't have a frame rate or audio codec, and any field can fail
+/// to probe even on an otherwise-supported file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaMetadata {
+    /// Duration in seconds. Videos only.
+    pub duration: Option<f64>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    /// Average frame rate, in frames per second. Videos only.
+    pub frame_rate: Option<f64>,
+    /// Container/demuxer name, e.g. `"mov,mp4,m4a,3gp,3g2,mj2"`. Videos only.
+    pub container: Option<String>,
+    /// Video codec name, e.g. `"h264"`. Videos only.
+    pub video_codec: Option<String>,
+    /// Audio codec name, e.g. `"aac"`. Videos only, and only if the video has an audio stream.
+    pub audio_codec: Option<String>,
+    /// Overall bitrate in bits per second. Videos only.
+    pub bitrate: Option<i64>,
+}
+
+/// Probes `path` for technical metadata appropriate to `media_kind`. Never fails: a file that
+/// can't be probed yields `MediaMetadata::default()`.
+pub fn probe(media_kind: MediaKind, path: &Path) -> MediaMetadata {
+    match media_kind {
+        MediaKind::Video => probe_video(path).unwrap_or_default(),
+        MediaKind::Image => probe_image(path).unwrap_or_default(),
+    }
+}
+
+fn probe_video(path: &Path) -> Option<MediaMetadata> {
+    ffmpeg::init().ok()?;
+    let input = ffmpeg::format::input(&path).ok()?;
+
+    let mut metadata = MediaMetadata {
+        container: Some(input.format().name().to_string()),
+        bitrate: positive_i64(input.bit_rate() as i64),
+        ..MediaMetadata::default()
+    };
+
+    // `duration()` is in AV_TIME_BASE units, i.e. microseconds.
+    let duration = input.duration();
+    if duration > 0 {
+        metadata.duration = Some(duration as f64 / 1_000_000.0);
+    }
+
+    if let Some(stream) = input.streams().best(ffmpeg::media::Type::Video) {
+        if let Ok(decoder_context) =
+            ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+        {
+            if let Ok(decoder) = decoder_context.decoder().video() {
+                metadata.width = positive_i64(i64::from(decoder.width()));
+                metadata.height = positive_i64(i64::from(decoder.height()));
+                metadata.video_codec = codec_name(decoder.id());
+            }
+        }
+        let rate = stream.rate();
+        if rate.denominator() != 0 {
+            metadata.frame_rate = Some(f64::from(rate.numerator()) / f64::from(rate.denominator()));
+        }
+    }
+
+    if let Some(stream) = input.streams().best(ffmpeg::media::Type::Audio) {
+        if let Ok(decoder_context) =
+            ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+        {
+            if let Ok(decoder) = decoder_context.decoder().audio() {
+                metadata.audio_codec = codec_name(decoder.id());
+            }
+        }
+    }
+
+    Some(metadata)
+}
+
+fn probe_image(path: &Path) -> Option<MediaMetadata> {
+    let image = image::open(path).ok()?;
+    Some(MediaMetadata {
+        width: positive_i64(i64::from(image.width())),
+        height: positive_i64(i64::from(image.height())),
+        ..MediaMetadata::default()
+    })
+}
+
+fn codec_name(id: ffmpeg::codec::Id) -> Option<String> {
+    if id == ffmpeg::codec::Id::None {
+        None
+    } else {
+        Some(format!("{id:?}").to_lowercase())
+    }
+}
+
+fn positive_i64(value: i64) -> Option<i64> {
+    if value > 0 {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probing_a_nonexistent_file_degrades_to_all_none() {
+        let metadata = probe(MediaKind::Video, Path::new("/nonexistent/file.mp4"));
+        assert_eq!(metadata, MediaMetadata::default());
+
+        let metadata = probe(MediaKind::Image, Path::new("/nonexistent/file.png"));
+        assert_eq!(metadata, MediaMetadata::default());
+    }
+
+    #[test]
+    fn positive_i64_rejects_zero_and_negative() {
+        assert_eq!(positive_i64(0), None);
+        assert_eq!(positive_i64(-1), None);
+        assert_eq!(positive_i64(42), Some(42));
+    }
+}
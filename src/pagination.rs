@@ -0,0 +1,126 @@
+//! Paginated, ordered listing over the items catalog, backing `Repo::list_items`.
+//!
+//! Modeled on rustypipe's `Paginator` + `ChannelOrder`: instead of `Filter`'s `get_items` (used by
+//! `Repo::get_files`) returning the entire matching set in one shot, `list_items` returns one
+//! `ItemPage` at a time plus an opaque `next_cursor` the caller feeds back in to continue. The
+//! cursor encodes the last row's sort key and its item hash as a tie-breaker, so pages are found
+//! by seeking (`WHERE (key, hash) > (?, ?) ORDER BY ... LIMIT ?`) rather than by page number/
+//! offset — an insert or delete elsewhere in the catalog between two calls can't shift
+//! already-returned items into a later page or duplicate them into the next one.
+//!
+//! `ItemOrder` is deliberately narrower than rustypipe's `ChannelOrder`: vorg's catalog only has
+//! columns for import time, title, and total size (summed across an item's chunks). Ordering by
+//! media creation time isn't implemented, since `metadata::MediaMetadata` has no creation-time
+//! field today — probing it would mean reading container tags ffmpeg doesn't currently look at,
+//! a separate feature. Filtering by studio is declined for the same reason `Filter` never grew a
+//! studio condition: there has never been a studio/studio_id column in this schema.
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::db::Item;
+
+/// How `Repo::list_items` orders its results. Every variant breaks ties by item hash, ascending
+/// for ascending orders and descending for descending ones, so the sequence is total even when
+/// many items share a sort key (e.g. the same title, or the same size).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemOrder {
+    /// Most recently imported first.
+    NewestImported,
+    /// Least recently imported first.
+    OldestImported,
+    /// Title, A to Z.
+    TitleAscending,
+    /// Title, Z to A.
+    TitleDescending,
+    /// Smallest total chunk size first.
+    SmallestFirst,
+    /// Largest total chunk size first.
+    LargestFirst,
+}
+
+/// One page of a `Repo::list_items` listing.
+pub struct ItemPage {
+    pub items: Vec<Item>,
+    next_cursor: Option<String>,
+}
+
+impl ItemPage {
+    pub(crate) fn new(items: Vec<Item>, next_cursor: Option<String>) -> Self {
+        ItemPage { items, next_cursor }
+    }
+
+    /// Whether this is the last page of the listing: `next_cursor` is `None` past this point.
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.next_cursor.is_none()
+    }
+
+    /// Opaque continuation token to pass as `list_items`'s `cursor` to fetch the next page, or
+    /// `None` if `is_exhausted()`.
+    #[must_use]
+    pub fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+}
+
+/// Encodes the sort key and hash of the last item on a page into an opaque cursor string.
+///
+/// The encoding itself (hex of a NUL-separated pair) isn't meant to be parsed by callers — only
+/// round-tripped back through `decode_cursor` — so it's fine that a title can itself contain most
+/// punctuation; `decode_cursor` splits at the first NUL, so a key that contains NUL does not
+/// round-trip.
+pub(crate) fn encode_cursor(key: &str, hash: &str) -> String {
+    hex::encode(format!("{key}\0{hash}"))
+}
+
+/// Decodes a cursor produced by `encode_cursor` back into `(key, hash)`.
+///
+/// # Errors
+/// - `ErrorKind::InvalidCursor` if `token` is not a cursor this module produced.
+pub(crate) fn decode_cursor(token: &str) -> Result<(String, String)> {
+    let invalid = || Error::new(ErrorKind::InvalidCursor, "pagination-cursor-invalid");
+    let bytes = hex::decode(token).map_err(|_| invalid())?;
+    let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+    let (key, hash) = text.split_once('\0').ok_or_else(invalid)?;
+    Ok((key.to_string(), hash.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_roundtrips_through_its_encoding() {
+        let token = encode_cursor("some title", "abc123");
+        let (key, hash) = decode_cursor(&token).unwrap();
+        assert_eq!(key, "some title");
+        assert_eq!(hash, "abc123");
+    }
+
+    #[test]
+    fn cursor_roundtrips_when_the_key_contains_the_separator_free_punctuation() {
+        let token = encode_cursor("title: part two | redux", "deadbeef");
+        let (key, hash) = decode_cursor(&token).unwrap();
+        assert_eq!(key, "title: part two | redux");
+        assert_eq!(hash, "deadbeef");
+    }
+
+    #[test]
+    fn decoding_garbage_is_an_error() {
+        let result = decode_cursor("not valid hex!!");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn page_with_a_next_cursor_is_not_exhausted() {
+        let page = ItemPage::new(Vec::new(), Some(encode_cursor("a", "b")));
+        assert!(!page.is_exhausted());
+        assert!(page.next_cursor().is_some());
+    }
+
+    #[test]
+    fn last_page_has_no_cursor() {
+        let page = ItemPage::new(Vec::new(), None);
+        assert!(page.is_exhausted());
+        assert!(page.next_cursor().is_none());
+    }
+}
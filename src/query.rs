@@ -0,0 +1,385 @@
+//! A small tag/title query language for `Repo::query_items`/`Repo::query_items_page` (wired up to
+//! the `query` CLI subcommand in `main.rs`), following the entry/attribute query approach used by
+//! upend's `lang::Query`: `tag:foo`, `title:"some words"` (routed through the `title_fts` MATCH
+//! path `db::DB::search_collections` also uses, or `ILIKE` on `PostgresDatabase`),
+//! `tag:namespace:*` for every tag under a namespace prefix (e.g. `tag:meta:*` matches
+//! `meta:Incomplete`), and boolean `AND`/`OR`/`NOT` grouping with parentheses, e.g.
+//! `tag:vacation AND NOT (title:"test clip" OR tag:draft)`.
+//!
+//! This is distinct from `filter::Filter`, the simpler builder `get_items`/`list_items_page` use:
+//! `Filter` can only AND its conditions together and has no negation, which is enough for most
+//! callers, but can't express "either of these two tags" or "not tagged X". `FilterExpr` exists for
+//! the cases that need that.
+
+use crate::error::{Error, ErrorKind, Result};
+
+/// A parsed filter expression, compiled to SQL by `db::DB::query_items`/`PostgresDatabase`'s
+/// `Database::query_items` impl.
+///
+/// An empty query string parses to `And(vec![])`, the vacuous-truth identity for AND, which
+/// compiles to "match everything" — preserving `get_items`'s historical no-filter behavior for
+/// callers who don't have a query to run yet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    And(Vec<FilterExpr>),
+    Or(Vec<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Tag(String),
+    /// Every tag starting with this namespace prefix (prefix includes the trailing `:`), e.g.
+    /// `tag:meta:*` parses to `TagPrefix("meta:")` and matches `meta:Incomplete`, `meta:Draft`, ...
+    TagPrefix(String),
+    Title(String),
+}
+
+/// Parses a query string into a `FilterExpr`.
+///
+/// # Errors
+/// - `ErrorKind::InvalidQuery` if `input` is not valid query syntax.
+pub fn parse(input: &str) -> Result<FilterExpr> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Ok(FilterExpr::And(Vec::new()));
+    }
+
+    let mut parser = Parser { tokens: &tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    if parser.pos != tokens.len() {
+        return Err(syntax_error("unexpected trailing input"));
+    }
+    Ok(expr)
+}
+
+fn syntax_error(detail: impl Into<String>) -> Error {
+    Error::with_args(
+        ErrorKind::InvalidQuery,
+        "query-syntax-invalid",
+        vec![("detail", detail.into())],
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Tag(String),
+    TagPrefix(String),
+    Title(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut lexer = Lexer {
+        chars: input.chars().peekable(),
+    };
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Lexer<'_> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(c) if c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    /// Consumes `prefix` if the remaining input starts with it, leaving the cursor untouched
+    /// otherwise.
+    fn consume_prefix(&mut self, prefix: &str) -> bool {
+        let mut lookahead = self.chars.clone();
+        for expected in prefix.chars() {
+            if lookahead.next() != Some(expected) {
+                return false;
+            }
+        }
+        self.chars = lookahead;
+        true
+    }
+
+    /// Reads a run of non-whitespace, non-parenthesis characters, e.g. a bare keyword or a
+    /// `tag:`'s argument.
+    fn read_bare_word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' {
+                break;
+            }
+            word.push(c);
+            self.chars.next();
+        }
+        word
+    }
+
+    /// Reads a `"..."` phrase, the argument to `title:`. `\"` escapes a literal quote; any other
+    /// character, including whitespace, is taken literally.
+    fn read_quoted_string(&mut self) -> Result<String> {
+        if self.chars.next() != Some('"') {
+            return Err(syntax_error("expected '\"' to start a title:\"...\" phrase"));
+        }
+        let mut phrase = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(phrase),
+                Some('\\') if self.chars.peek() == Some(&'"') => {
+                    phrase.push('"');
+                    self.chars.next();
+                }
+                Some(c) => phrase.push(c),
+                None => return Err(syntax_error("unterminated title:\"...\" phrase")),
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            None => Ok(None),
+            Some('(') => {
+                self.chars.next();
+                Ok(Some(Token::LParen))
+            }
+            Some(')') => {
+                self.chars.next();
+                Ok(Some(Token::RParen))
+            }
+            Some(_) => {
+                if self.consume_prefix("tag:") {
+                    let name = self.read_bare_word();
+                    if name.is_empty() {
+                        return Err(syntax_error("expected a tag name after \"tag:\""));
+                    }
+                    if let Some(prefix) = name.strip_suffix('*') {
+                        if prefix.is_empty() {
+                            return Err(syntax_error(
+                                "expected a namespace before \"*\" in \"tag:namespace:*\"",
+                            ));
+                        }
+                        Ok(Some(Token::TagPrefix(prefix.to_string())))
+                    } else {
+                        Ok(Some(Token::Tag(name)))
+                    }
+                } else if self.consume_prefix("title:") {
+                    Ok(Some(Token::Title(self.read_quoted_string()?)))
+                } else {
+                    let word = self.read_bare_word();
+                    match word.as_str() {
+                        "AND" => Ok(Some(Token::And)),
+                        "OR" => Ok(Some(Token::Or)),
+                        "NOT" => Ok(Some(Token::Not)),
+                        _ => Err(syntax_error(format!("unexpected token \"{word}\""))),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Recursive-descent parser over already-lexed `tokens`, implementing (in ascending precedence)
+/// `OR`, `AND`, `NOT`, and parenthesized/leaf primaries.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr> {
+        let mut terms = vec![self.parse_and()?];
+        while matches!(self.peek(), Some(Token::Or)) {
+            self.advance();
+            terms.push(self.parse_and()?);
+        }
+        Ok(one_or_combine(terms, FilterExpr::Or))
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr> {
+        let mut terms = vec![self.parse_not()?];
+        while matches!(self.peek(), Some(Token::And)) {
+            self.advance();
+            terms.push(self.parse_not()?);
+        }
+        Ok(one_or_combine(terms, FilterExpr::And))
+    }
+
+    fn parse_not(&mut self) -> Result<FilterExpr> {
+        if matches!(self.peek(), Some(Token::Not)) {
+            self.advance();
+            return Ok(FilterExpr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<FilterExpr> {
+        match self.advance() {
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.advance() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(syntax_error("expected a closing \")\"")),
+                }
+            }
+            Some(Token::Tag(name)) => Ok(FilterExpr::Tag(name.clone())),
+            Some(Token::TagPrefix(prefix)) => Ok(FilterExpr::TagPrefix(prefix.clone())),
+            Some(Token::Title(text)) => Ok(FilterExpr::Title(text.clone())),
+            other => Err(syntax_error(format!(
+                "expected \"tag:\", \"title:\", \"NOT\", or \"(\", found {other:?}"
+            ))),
+        }
+    }
+}
+
+/// Returns `terms`' sole element directly, or wraps more than one in `combine`. Keeps a
+/// single-term `AND`/`OR` from nesting pointlessly in the resulting tree.
+fn one_or_combine(
+    mut terms: Vec<FilterExpr>,
+    combine: impl FnOnce(Vec<FilterExpr>) -> FilterExpr,
+) -> FilterExpr {
+    if terms.len() == 1 {
+        terms.remove(0)
+    } else {
+        combine(terms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_query_is_match_everything() {
+        assert_eq!(parse("").unwrap(), FilterExpr::And(Vec::new()));
+        assert_eq!(parse("   ").unwrap(), FilterExpr::And(Vec::new()));
+    }
+
+    #[test]
+    fn single_tag() {
+        assert_eq!(parse("tag:vacation").unwrap(), FilterExpr::Tag(String::from("vacation")));
+    }
+
+    #[test]
+    fn tag_prefix_wildcard() {
+        assert_eq!(
+            parse("tag:meta:*").unwrap(),
+            FilterExpr::TagPrefix(String::from("meta:"))
+        );
+    }
+
+    #[test]
+    fn bare_wildcard_is_a_syntax_error() {
+        let result = parse("tag:*");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err().kind, ErrorKind::InvalidQuery));
+    }
+
+    #[test]
+    fn quoted_title() {
+        assert_eq!(
+            parse("title:\"some words\"").unwrap(),
+            FilterExpr::Title(String::from("some words"))
+        );
+    }
+
+    #[test]
+    fn title_with_escaped_quote() {
+        assert_eq!(
+            parse("title:\"say \\\"hi\\\"\"").unwrap(),
+            FilterExpr::Title(String::from("say \"hi\""))
+        );
+    }
+
+    #[test]
+    fn and_combines_two_terms() {
+        assert_eq!(
+            parse("tag:a AND tag:b").unwrap(),
+            FilterExpr::And(vec![
+                FilterExpr::Tag(String::from("a")),
+                FilterExpr::Tag(String::from("b")),
+            ])
+        );
+    }
+
+    #[test]
+    fn or_combines_two_terms() {
+        assert_eq!(
+            parse("tag:a OR tag:b").unwrap(),
+            FilterExpr::Or(vec![
+                FilterExpr::Tag(String::from("a")),
+                FilterExpr::Tag(String::from("b")),
+            ])
+        );
+    }
+
+    #[test]
+    fn not_negates_a_term() {
+        assert_eq!(
+            parse("NOT tag:draft").unwrap(),
+            FilterExpr::Not(Box::new(FilterExpr::Tag(String::from("draft"))))
+        );
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("tag:a OR tag:b AND tag:c").unwrap(),
+            FilterExpr::Or(vec![
+                FilterExpr::Tag(String::from("a")),
+                FilterExpr::And(vec![
+                    FilterExpr::Tag(String::from("b")),
+                    FilterExpr::Tag(String::from("c")),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        assert_eq!(
+            parse("tag:a AND NOT (title:\"test clip\" OR tag:draft)").unwrap(),
+            FilterExpr::And(vec![
+                FilterExpr::Tag(String::from("a")),
+                FilterExpr::Not(Box::new(FilterExpr::Or(vec![
+                    FilterExpr::Title(String::from("test clip")),
+                    FilterExpr::Tag(String::from("draft")),
+                ]))),
+            ])
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_is_a_syntax_error() {
+        let result = parse("title:\"oops");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err().kind, ErrorKind::InvalidQuery));
+    }
+
+    #[test]
+    fn unmatched_paren_is_a_syntax_error() {
+        let result = parse("(tag:a");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err().kind, ErrorKind::InvalidQuery));
+    }
+
+    #[test]
+    fn trailing_garbage_is_a_syntax_error() {
+        let result = parse("tag:a tag:b");
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err().kind, ErrorKind::InvalidQuery));
+    }
+}
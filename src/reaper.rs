@@ -0,0 +1,118 @@
+//! Background-reclaim pass driven by `Repo::sweep`, mirroring `repair`'s
+//! findings-driven-fixer shape but for two different kinds of deferred cleanup:
+//!
+//! - items still tagged `meta:Incomplete` whose `imported_at` is older than a configurable TTL
+//!   (they were likely abandoned mid-import rather than ever getting their metadata filled in;
+//!   see `filter`'s module doc), and
+//! - chunks that sit in the store with no `items`/`item_chunks` row referencing them at all,
+//!   e.g. left behind by a process that was killed between writing a chunk and committing the db
+//!   row that references it.
+//!
+//! Both passes reuse pieces `Repo` already has: `Filter::with_tag("meta:Incomplete")` for the
+//! first, `utils::reconcile_sorted_hashes` (the same db-vs-store reconciliation
+//! `check_data_integrity` already does) for the second.
+//!
+//! `spawn_auto_sweep` is the opt-in automatic counterpart to the plain on-demand `Repo::sweep`:
+//! callers who don't want to wire their own cron job or CLI invocation around `sweep` can have
+//! this module do it for them instead, on a timer or an explicit ping.
+
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::Repo;
+
+/// Handle to a background sweep task started by `spawn_auto_sweep`.
+///
+/// Dropping this handle closes its ping channel, which tells the task to stop once its current
+/// sweep (if any) finishes — it does not abort a sweep in progress. There's no detached mode: a
+/// `Repo` left sweeping in the background with nobody holding its handle could never be told to
+/// stop.
+pub struct AutoSweepHandle {
+    ping: mpsc::Sender<()>,
+    #[allow(dead_code)] // kept so the task outlives the handle that spawned it, nothing to join
+    task: JoinHandle<()>,
+}
+
+impl AutoSweepHandle {
+    /// Wakes the background task immediately, rather than waiting for its next timer tick. Useful
+    /// right after a caller knows a batch of imports just finished and wants stale
+    /// `meta:Incomplete` items and orphaned chunks reclaimed sooner than the next scheduled sweep.
+    ///
+    /// Silently does nothing if the task's ping queue is already full (a sweep is already about to
+    /// run) or the task has stopped.
+    pub fn ping(&self) {
+        let _ = self.ping.try_send(());
+    }
+}
+
+/// Spawns a task that calls `Repo::sweep` every `interval`, or immediately whenever
+/// `AutoSweepHandle::ping` is called.
+///
+/// Takes ownership of `repo` rather than sharing it behind a lock with whatever else might use
+/// it: `sweep`'s own grace-period wait (see its doc comment) can run for minutes, and a shared
+/// `&mut Repo` held across that whole wait would stall every other operation on it for just as
+/// long — precisely the kind of stall `sweep`'s grace period exists to tolerate in *other*
+/// processes, not manufacture in this one. Point this at a repo directory a second time instead
+/// (`Repo::new` against the same path), the same way an external cron job or another process
+/// would; chunk/db storage already has to tolerate concurrent access from independent `Repo`
+/// handles for the grace period to make sense at all.
+///
+/// A sweep that errors is reported and otherwise ignored — there's no caller left to hand the
+/// error to once this is running detached, and nothing about a single failed pass makes the next
+/// scheduled one unsafe to attempt.
+pub fn spawn_auto_sweep(mut repo: Repo, interval: Duration) -> AutoSweepHandle {
+    let (ping_tx, mut ping_rx) = mpsc::channel(1);
+
+    let task = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // A sweep can itself run for as long as `sweep_grace_period` (see `Repo::sweep`'s doc
+        // comment), easily longer than `interval`; catch up with a single delayed tick rather
+        // than firing every tick missed while a sweep ran back-to-back.
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick fires immediately; skip it so the first sweep runs one `interval` after
+        // spawning (or on the first ping) rather than right at startup.
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                woken = ping_rx.recv() => {
+                    if woken.is_none() {
+                        // Every `AutoSweepHandle` was dropped; stop rather than sweep forever with
+                        // nobody left to observe the result or ping us again.
+                        break;
+                    }
+                }
+            }
+
+            if let Err(error) = repo.sweep().await {
+                eprintln!("Background sweep failed: {error}. Will retry on the next tick.");
+            }
+        }
+    });
+
+    AutoSweepHandle { ping: ping_tx, task }
+}
+
+/// Outcome of `Repo::sweep`.
+#[derive(Debug, Default)]
+pub struct SweepSummary {
+    /// Hashes of `meta:Incomplete` items deleted for having outlived the configured TTL.
+    pub expired_items: Vec<String>,
+    /// Chunk hashes whose `refcount` dropped to zero as a direct result of deleting
+    /// `expired_items`, and whose blobs were therefore deleted from the store too.
+    pub reclaimed_chunks: Vec<String>,
+    /// Chunk hashes found in the store with no referencing db row at all, deleted by the
+    /// reconciliation pass.
+    pub orphaned_chunks: Vec<String>,
+}
+
+impl SweepSummary {
+    /// Total number of blobs this sweep removed from the store, across both the TTL-expiry pass
+    /// and the orphan-reconciliation pass.
+    pub fn total_blobs_removed(&self) -> usize {
+        self.reclaimed_chunks.len() + self.orphaned_chunks.len()
+    }
+}
@@ -0,0 +1,47 @@
+//! Repair pass driven by `Repo::check_data_integrity` findings.
+//!
+//! Modeled on statix's multipass fixer: `Repo::repair` re-runs the integrity check and acts on
+//! what it reports, repeating until the check comes back clean or a pass makes no further
+//! progress, capped at `MAX_PASSES` to avoid looping forever on an unfixable repo.
+
+use similar::{ChangeTag, TextDiff};
+
+/// Safety cap on repair passes, in case fixes keep uncovering new problems.
+pub const MAX_PASSES: usize = 10;
+
+/// A single repair pass: the actions taken (or, in dry-run mode, that would have been taken).
+pub type Pass = Vec<String>;
+
+/// Outcome of `Repo::repair`.
+#[derive(Debug, Default)]
+pub struct RepairSummary {
+    /// One entry per pass that found problems, in order; a final pass that could act on none of
+    /// its findings leaves an empty entry. A dry run stops after its first pass.
+    pub passes: Vec<Pass>,
+    /// Findings that cannot be fixed automatically and need manual review (missing or
+    /// hash-mismatched chunks, missing thumbnails).
+    pub unresolved: Vec<String>,
+}
+
+impl RepairSummary {
+    /// Total number of actions taken (or, in dry-run mode, proposed) across all passes.
+    pub fn total_actions(&self) -> usize {
+        self.passes.iter().map(Vec::len).sum()
+    }
+}
+
+/// Renders a human-readable diff of the actions a repair pass would take, for `--dry-run`.
+pub fn describe_dry_run(actions: &[String]) -> String {
+    let after = actions.join("\n");
+    TextDiff::from_lines("", &after)
+        .iter_all_changes()
+        .map(|change| {
+            let sign = match change.tag() {
+                ChangeTag::Delete => "-",
+                ChangeTag::Insert => "+",
+                ChangeTag::Equal => " ",
+            };
+            format!("{sign}{change}")
+        })
+        .collect()
+}
@@ -0,0 +1,210 @@
+//! Structured findings produced by `Repo::check_data_integrity`.
+//!
+//! Turns `compare_lists` from an internal helper into the backbone of an auditable report: each
+//! problem becomes a `Finding` carrying its category, the affected chunk, and expected-vs-actual
+//! hashes, instead of a free-form string. `main` can then render the same findings as pretty text
+//! (the historical default) or as JSON for scripting into CI or cron.
+
+use std::fmt;
+
+/// Category of a single integrity finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FindingCategory {
+    /// A chunk the db references that is missing from the store.
+    ChunkMissing,
+    /// A chunk present in the store but not referenced by any item in the db.
+    ChunkUnexpected,
+    /// A chunk whose on-disk content does not hash to its filename.
+    ChunkHashMismatch,
+    /// An item in the db has no corresponding thumbnail file.
+    ThumbnailMissing,
+    /// An item's recorded extension disagrees with the extension re-derived from its actual
+    /// content (the same libmagic sniffing `import_file` used to assign one in the first place).
+    ExtensionMismatch,
+}
+
+impl fmt::Display for FindingCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            FindingCategory::ChunkMissing => "chunk-missing",
+            FindingCategory::ChunkUnexpected => "chunk-unexpected",
+            FindingCategory::ChunkHashMismatch => "chunk-hash-mismatch",
+            FindingCategory::ThumbnailMissing => "thumbnail-missing",
+            FindingCategory::ExtensionMismatch => "extension-mismatch",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// A single integrity finding.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub category: FindingCategory,
+    /// Identifies what the finding is about: the chunk hash for chunk findings, or the item hash
+    /// for `ThumbnailMissing`/`ExtensionMismatch`.
+    pub path: String,
+    /// Hash vorg expected at `path` for `ChunkHashMismatch`, or the extension recorded in the db
+    /// for `ExtensionMismatch`. Unset otherwise.
+    pub expected_hash: Option<String>,
+    /// Hash vorg actually found at `path` for `ChunkHashMismatch`, or the extension re-derived
+    /// from the item's content for `ExtensionMismatch`. Unset otherwise.
+    pub actual_hash: Option<String>,
+    /// Whether this finding was verified byte-for-byte against the store, or only against a
+    /// cached manifest (see `RepoOptions::online`).
+    pub verified: bool,
+}
+
+impl Finding {
+    fn cache_suffix(&self) -> &'static str {
+        if self.verified {
+            ""
+        } else {
+            " (cache-only, unverified)"
+        }
+    }
+
+    /// Renders the finding the same way `check_data_integrity` printed it before this structured
+    /// report existed, so `--format text` output is unchanged.
+    fn to_text_line(&self) -> String {
+        match self.category {
+            FindingCategory::ChunkMissing => format!(
+                "chunk{}: chunk not found in store: {}",
+                self.cache_suffix(),
+                self.path
+            ),
+            FindingCategory::ChunkUnexpected => format!(
+                "chunk{}: redundant chunk in store: {}",
+                self.cache_suffix(),
+                self.path
+            ),
+            FindingCategory::ChunkHashMismatch => format!(
+                "hash: Expected {}, but real hash is {}",
+                self.expected_hash.as_deref().unwrap_or(""),
+                self.actual_hash.as_deref().unwrap_or("")
+            ),
+            FindingCategory::ThumbnailMissing => {
+                format!("thumbnail: no thumbnail for item {}", self.path)
+            }
+            FindingCategory::ExtensionMismatch => format!(
+                "ext: different extensions: {} in db but {} in store",
+                self.expected_hash.as_deref().unwrap_or(""),
+                self.actual_hash.as_deref().unwrap_or("")
+            ),
+        }
+    }
+
+    /// Serializes the finding as a single-line JSON object.
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"category\":\"{}\",\"path\":\"{}\",\"expected_hash\":{},\"actual_hash\":{},\"verified\":{}}}",
+            self.category,
+            json_escape(&self.path),
+            json_string_or_null(self.expected_hash.as_deref()),
+            json_string_or_null(self.actual_hash.as_deref()),
+            self.verified
+        )
+    }
+}
+
+fn json_string_or_null(value: Option<&str>) -> String {
+    match value {
+        Some(value) => format!("\"{}\"", json_escape(value)),
+        None => String::from("null"),
+    }
+}
+
+/// Escapes `value` for embedding in a JSON string literal: backslashes, double quotes, and the
+/// control characters RFC 8259 forbids literally inside a JSON string. `pub(crate)` so
+/// `db::item_to_json` (`DB::export_json`) can reuse it rather than re-implementing escaping.
+pub(crate) fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Serializes a full report as a JSON array, one object per finding, for `--format json`.
+pub fn to_json(findings: &[Finding]) -> String {
+    let items: Vec<String> = findings.iter().map(Finding::to_json).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Renders a full report the way the old free-form `check_data_integrity` string looked, for
+/// `--format text` (the default).
+pub fn to_text(findings: &[Finding]) -> String {
+    findings
+        .iter()
+        .map(|finding| finding.to_text_line() + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_chunk_missing_as_text() {
+        let finding = Finding {
+            category: FindingCategory::ChunkMissing,
+            path: String::from("abc123"),
+            expected_hash: None,
+            actual_hash: None,
+            verified: true,
+        };
+        assert_eq!(to_text(&[finding]), "chunk: chunk not found in store: abc123\n");
+    }
+
+    #[test]
+    fn renders_cache_only_finding_as_text() {
+        let finding = Finding {
+            category: FindingCategory::ChunkUnexpected,
+            path: String::from("abc123"),
+            expected_hash: None,
+            actual_hash: None,
+            verified: false,
+        };
+        assert_eq!(
+            to_text(&[finding]),
+            "chunk (cache-only, unverified): redundant chunk in store: abc123\n"
+        );
+    }
+
+    #[test]
+    fn renders_extension_mismatch_as_text() {
+        let finding = Finding {
+            category: FindingCategory::ExtensionMismatch,
+            path: String::from("abc123"),
+            expected_hash: Some(String::from("avi")),
+            actual_hash: Some(String::from("mp4")),
+            verified: true,
+        };
+        assert_eq!(
+            to_text(&[finding]),
+            "ext: different extensions: avi in db but mp4 in store\n"
+        );
+    }
+
+    #[test]
+    fn renders_hash_mismatch_as_json() {
+        let finding = Finding {
+            category: FindingCategory::ChunkHashMismatch,
+            path: String::from("abc123"),
+            expected_hash: Some(String::from("abc123")),
+            actual_hash: Some(String::from("def456")),
+            verified: true,
+        };
+        assert_eq!(
+            to_json(&[finding]),
+            "[{\"category\":\"chunk-hash-mismatch\",\"path\":\"abc123\",\"expected_hash\":\"abc123\",\"actual_hash\":\"def456\",\"verified\":true}]"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Pluggable content store backing `store/chunks`.
+//!
+//! `Repo` used to call `fs::rename`/`fs::copy`/`fs::read_dir` directly against
+//! `<repo>/store/chunks`, which permanently tied the chunk store to the local filesystem. The
+//! `Store` trait decouples the hash-prefixed layout (`{hash[0..2]}/{hash[2..]}`) from the
+//! persistence mechanism, so the same `Repo` logic can back onto remote object storage, or an
+//! in-memory store for hermetic tests, by swapping the `Arc<dyn Store>` it holds. `Repo` holds it
+//! behind an `Arc` rather than a `Box` so `check_data_integrity` can share it across the
+//! concurrent tasks that re-hash chunks in parallel.
+
+use crate::error::{Error, Result};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Content-addressed chunk storage, keyed by chunk hash.
+///
+/// Implementations need not be transactional across calls; `Repo` only ever writes a chunk once
+/// (the hash is the content, so a second `put` of the same hash is redundant) and asks the db
+/// whether it already knows the chunk (`Database::chunks_exist`/`chunk_exists`) to avoid that.
+pub trait Store: Send + Sync {
+    /// Writes `data` under `hash`. Overwrites any existing content at `hash`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::IO` if the underlying storage operation fails.
+    fn put(&self, hash: &str, data: &[u8]) -> Result<()>;
+
+    /// Reads back the content previously `put` under `hash`.
+    ///
+    /// # Errors
+    /// - `ErrorKind::IO` if `hash` is not present, or the read fails.
+    fn get(&self, hash: &str) -> Result<Vec<u8>>;
+
+    /// Whether `hash` has been `put` into the store.
+    fn exists(&self, hash: &str) -> bool;
+
+    /// Lists every hash currently in the store, in no particular order.
+    ///
+    /// # Errors
+    /// - `ErrorKind::IO` if the store cannot be enumerated.
+    fn list(&self) -> Result<Vec<String>>;
+
+    /// Removes `hash` from the store. A no-op if `hash` is not present.
+    ///
+    /// # Errors
+    /// - `ErrorKind::IO` if the underlying storage operation fails.
+    fn delete(&self, hash: &str) -> Result<()>;
+}
+
+/// Default `Store`: a hash-prefixed directory tree rooted at `<repo>/store/chunks`, in the
+/// `{hash[0..2]}/{hash[2..]}` layout described in the module doc.
+pub struct LocalFsStore {
+    root: PathBuf,
+}
+
+impl LocalFsStore {
+    /// `root` is the repo's `store/chunks` directory.
+    pub fn new<T>(root: T) -> Self
+    where
+        T: AsRef<Path>,
+    {
+        LocalFsStore {
+            root: root.as_ref().to_owned(),
+        }
+    }
+
+    fn chunk_path(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[0..2]).join(&hash[2..])
+    }
+}
+
+impl Store for LocalFsStore {
+    fn put(&self, hash: &str, data: &[u8]) -> Result<()> {
+        let path = self.chunk_path(hash);
+        let subfolder = path.parent().expect("Chunk path must have a parent");
+        fs::create_dir_all(subfolder)?;
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        Ok(fs::read(self.chunk_path(hash))?)
+    }
+
+    fn exists(&self, hash: &str) -> bool {
+        self.chunk_path(hash).is_file()
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let mut hashes = Vec::new();
+        if self.root.is_dir() {
+            list_recursive(&self.root, &mut hashes)?;
+        }
+        Ok(hashes)
+    }
+
+    fn delete(&self, hash: &str) -> Result<()> {
+        let path = self.chunk_path(hash);
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn list_recursive(dir: &Path, hashes: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            list_recursive(&path, hashes)?;
+        } else {
+            let hash = path
+                .parent()
+                .expect("Chunk must have a parent")
+                .file_name()
+                .expect("Chunk parent must have a filename.")
+                .to_string_lossy()
+                + path
+                    .file_name()
+                    .expect("Chunk must have a filename.")
+                    .to_string_lossy();
+            hashes.push(hash.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// In-memory `Store`, for hermetic tests that would otherwise need a real temp directory.
+#[derive(Default)]
+pub struct MemoryStore {
+    chunks: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl Store for MemoryStore {
+    fn put(&self, hash: &str, data: &[u8]) -> Result<()> {
+        self.chunks
+            .lock()
+            .expect("Memory store mutex poisoned.")
+            .insert(hash.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        self.chunks
+            .lock()
+            .expect("Memory store mutex poisoned.")
+            .get(hash)
+            .cloned()
+            .ok_or_else(|| Error::from(std::io::Error::from(std::io::ErrorKind::NotFound)))
+    }
+
+    fn exists(&self, hash: &str) -> bool {
+        self.chunks
+            .lock()
+            .expect("Memory store mutex poisoned.")
+            .contains_key(hash)
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        Ok(self
+            .chunks
+            .lock()
+            .expect("Memory store mutex poisoned.")
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn delete(&self, hash: &str) -> Result<()> {
+        self.chunks
+            .lock()
+            .expect("Memory store mutex poisoned.")
+            .remove(hash);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_store_roundtrips_put_and_get() {
+        let store = MemoryStore::new();
+        store.put("abc123", b"hello").unwrap();
+        assert!(store.exists("abc123"));
+        assert_eq!(store.get("abc123").unwrap(), b"hello");
+    }
+
+    #[test]
+    fn memory_store_lists_every_put_hash() {
+        let store = MemoryStore::new();
+        store.put("abc123", b"hello").unwrap();
+        store.put("def456", b"world").unwrap();
+        let mut hashes = store.list().unwrap();
+        hashes.sort();
+        assert_eq!(hashes, vec!["abc123".to_string(), "def456".to_string()]);
+    }
+
+    #[test]
+    fn memory_store_delete_removes_chunk() {
+        let store = MemoryStore::new();
+        store.put("abc123", b"hello").unwrap();
+        store.delete("abc123").unwrap();
+        assert!(!store.exists("abc123"));
+    }
+}
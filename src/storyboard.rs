@@ -0,0 +1,337 @@
+//! Storyboard sprite-sheet thumbnails: a grid of evenly-sampled video frames packed into one
+//! JPEG, for scrub-preview without decoding the whole video. Mirrors rustypipe's `Frameset`
+//! storyboard model and spacedrive's thumbstrips.
+//!
+//! Unlike the single-frame thumbnails in `thumbnail`, a storyboard sheet is content-addressed and
+//! written through the pluggable `store::Store` (not `<repo>/thumbnail`): identical input frames
+//! always hash to the same sheet, the same way identical chunk content always does, so
+//! re-importing the same video never duplicates its sheet. A small sidecar record — rows, cols,
+//! tile dimensions, and each tile's source timestamp — is written alongside it under a second,
+//! derived key, since the sheet bytes alone don't say how to slice the grid back apart.
+//!
+//! Frame decoding and scaling reuse `thumbnail`'s helpers; what's new here is sampling several
+//! timestamps instead of one and packing the results into a grid.
+
+use crate::{
+    chunking,
+    error::{Error, ErrorKind, Result},
+    store::Store,
+    thumbnail,
+};
+use ffmpeg_next as ffmpeg;
+
+/// Smallest gap, in seconds, storyboard accepts between sampled timestamps. Clamps how many tiles
+/// a very short clip actually gets, so it isn't asked to sample more distinct moments than it has.
+const MIN_SAMPLE_INTERVAL_SECONDS: f64 = 0.2;
+
+/// Controls how a storyboard sheet is generated.
+#[derive(Debug, Clone)]
+pub struct StoryboardOptions {
+    /// How many frames to sample across the video's duration, before clamping for short clips.
+    pub tile_count: u32,
+    /// Longest edge, in pixels, each tile is scaled to, preserving aspect ratio.
+    pub tile_size: u32,
+}
+
+impl Default for StoryboardOptions {
+    fn default() -> Self {
+        StoryboardOptions {
+            tile_count: 25,
+            tile_size: 160,
+        }
+    }
+}
+
+/// Where a generated storyboard ended up in the `Store`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoryboardHandle {
+    /// Content hash of the sprite sheet JPEG, as stored by `Store::put`.
+    pub sheet_hash: String,
+    /// Content hash of the sidecar record describing the sheet's layout, as stored by
+    /// `Store::put`. Use `read_sidecar` to parse it back into a `StoryboardSidecar`.
+    pub sidecar_hash: String,
+}
+
+/// Layout of a storyboard sheet: how to slice its grid back into individual tiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoryboardSidecar {
+    pub rows: u32,
+    pub cols: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Source timestamp, in seconds, of each tile, in row-major (left-to-right, top-to-bottom)
+    /// order.
+    pub timestamps: Vec<f64>,
+}
+
+/// Generates a storyboard sheet for the video at `video_path`, writing both the sheet and its
+/// sidecar into `store`.
+///
+/// Returns `Ok(None)` without writing anything if `video_path` has no decodable video stream or no
+/// usable duration: this is not an error, the same way a missing single-frame thumbnail isn't.
+///
+/// # Errors
+/// - `ErrorKind::Thumbnail` if opening, decoding, scaling, or encoding a sampled frame fails.
+/// - `ErrorKind::IO` if writing the sheet or sidecar to `store` fails.
+pub fn generate<T>(
+    video_path: T,
+    store: &dyn Store,
+    options: &StoryboardOptions,
+) -> Result<Option<StoryboardHandle>>
+where
+    T: AsRef<std::path::Path>,
+{
+    let video_path = video_path.as_ref();
+    ffmpeg::init().map_err(thumbnail::thumbnail_error)?;
+
+    let duration_seconds = {
+        let input = ffmpeg::format::input(&video_path).map_err(thumbnail::thumbnail_error)?;
+        if input.streams().best(ffmpeg::media::Type::Video).is_none() {
+            return Ok(None);
+        }
+        let duration = input.duration();
+        if duration <= 0 {
+            return Ok(None);
+        }
+        duration as f64 / 1_000_000.0
+    };
+
+    let timestamps = sample_timestamps(duration_seconds, options.tile_count);
+    if timestamps.is_empty() {
+        return Ok(None);
+    }
+
+    let mut tiles = Vec::with_capacity(timestamps.len());
+    for &timestamp in &timestamps {
+        let Some(frame) = decode_frame_at(video_path, timestamp, options.tile_size)? else {
+            continue;
+        };
+        tiles.push(frame);
+    }
+    if tiles.is_empty() {
+        return Ok(None);
+    }
+
+    let cols = (tiles.len() as f64).sqrt().ceil() as u32;
+    let rows = (tiles.len() as u32).div_ceil(cols);
+    let tile_width = tiles.iter().map(image::RgbImage::width).max().unwrap_or(0);
+    let tile_height = tiles.iter().map(image::RgbImage::height).max().unwrap_or(0);
+
+    let sheet = pack_grid(&tiles, rows, cols, tile_width, tile_height);
+    let sheet_bytes = encode_jpeg(&sheet)?;
+    let sheet_hash = chunking::hash_chunk(&sheet_bytes);
+    store.put(&sheet_hash, &sheet_bytes)?;
+
+    let sidecar = StoryboardSidecar {
+        rows,
+        cols,
+        tile_width,
+        tile_height,
+        timestamps: timestamps.into_iter().take(tiles.len()).collect(),
+    };
+    let sidecar_bytes = encode_sidecar(&sidecar);
+    let sidecar_hash = chunking::hash_chunk(&sidecar_bytes);
+    store.put(&sidecar_key(&sheet_hash, &sidecar_hash), &sidecar_bytes)?;
+
+    Ok(Some(StoryboardHandle {
+        sheet_hash,
+        sidecar_hash,
+    }))
+}
+
+/// Reads back the sidecar written alongside `handle.sheet_hash` by `generate`.
+///
+/// # Errors
+/// - `ErrorKind::IO` if the sidecar cannot be read from `store`.
+/// - `ErrorKind::Thumbnail` if the sidecar content is malformed.
+pub fn read_sidecar(store: &dyn Store, handle: &StoryboardHandle) -> Result<StoryboardSidecar> {
+    let bytes = store.get(&sidecar_key(&handle.sheet_hash, &handle.sidecar_hash))?;
+    decode_sidecar(&bytes)
+}
+
+/// The key a sidecar is stored under: derived from both hashes so that re-generating the same
+/// sheet (same `sheet_hash`) with a different sidecar content can't collide with it.
+fn sidecar_key(sheet_hash: &str, sidecar_hash: &str) -> String {
+    format!("{sheet_hash}-{sidecar_hash}.sidecar")
+}
+
+/// Evenly spaces up to `tile_count` timestamps (in seconds) across `[0, duration_seconds)`,
+/// clamping the count down so consecutive timestamps are never closer than
+/// `MIN_SAMPLE_INTERVAL_SECONDS` apart.
+fn sample_timestamps(duration_seconds: f64, tile_count: u32) -> Vec<f64> {
+    let max_distinct = (duration_seconds / MIN_SAMPLE_INTERVAL_SECONDS).floor() as u32;
+    let count = tile_count.min(max_distinct.max(1)).max(1);
+    (0..count)
+        .map(|index| duration_seconds * f64::from(index) / f64::from(count))
+        .collect()
+}
+
+/// Opens `video_path` fresh, seeks to `timestamp_seconds`, and decodes+scales the next frame.
+/// Returns `Ok(None)` if no frame could be decoded after seeking.
+fn decode_frame_at(
+    video_path: &std::path::Path,
+    timestamp_seconds: f64,
+    tile_size: u32,
+) -> Result<Option<image::RgbImage>> {
+    let mut input = ffmpeg::format::input(&video_path).map_err(thumbnail::thumbnail_error)?;
+    let Some(stream) = input.streams().best(ffmpeg::media::Type::Video) else {
+        return Ok(None);
+    };
+    let video_stream_index = stream.index();
+    let decoder_context = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+        .map_err(thumbnail::thumbnail_error)?;
+    let mut decoder = decoder_context
+        .decoder()
+        .video()
+        .map_err(thumbnail::thumbnail_error)?;
+
+    let target = (timestamp_seconds * 1_000_000.0) as i64;
+    if target > 0 {
+        let _ = input.seek(target, ..target);
+    }
+
+    let Some(frame) = thumbnail::decode_first_frame(&mut input, &mut decoder, video_stream_index)?
+    else {
+        return Ok(None);
+    };
+
+    let scaled = thumbnail::scale_frame(&frame, tile_size).map_err(thumbnail::thumbnail_error)?;
+    Ok(Some(thumbnail::video_frame_to_image(&scaled)?))
+}
+
+/// Packs `tiles` into a `rows` by `cols` grid, each cell `tile_width` by `tile_height`. Tiles
+/// smaller than the cell (a shorter final row, or a frame ffmpeg decoded at a slightly different
+/// size) are placed flush top-left, leaving black padding around them.
+fn pack_grid(
+    tiles: &[image::RgbImage],
+    rows: u32,
+    cols: u32,
+    tile_width: u32,
+    tile_height: u32,
+) -> image::RgbImage {
+    let mut sheet = image::RgbImage::new(cols * tile_width, rows * tile_height);
+    for (index, tile) in tiles.iter().enumerate() {
+        let col = index as u32 % cols;
+        let row = index as u32 / cols;
+        let origin_x = col * tile_width;
+        let origin_y = row * tile_height;
+        image::imageops::overlay(&mut sheet, tile, i64::from(origin_x), i64::from(origin_y));
+    }
+    sheet
+}
+
+fn encode_jpeg(image: &image::RgbImage) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageFormat::Jpeg,
+        )
+        .map_err(thumbnail::thumbnail_error)?;
+    Ok(bytes)
+}
+
+/// Serializes a sidecar as plain `key=value` lines, hand-rolled like the `manifest::Manifest`
+/// format rather than pulling in a serialization crate for one small record.
+fn encode_sidecar(sidecar: &StoryboardSidecar) -> Vec<u8> {
+    let timestamps = sidecar
+        .timestamps
+        .iter()
+        .map(f64::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "rows={}\ncols={}\ntile_width={}\ntile_height={}\ntimestamps={timestamps}\n",
+        sidecar.rows, sidecar.cols, sidecar.tile_width, sidecar.tile_height,
+    )
+    .into_bytes()
+}
+
+fn decode_sidecar(bytes: &[u8]) -> Result<StoryboardSidecar> {
+    let text = String::from_utf8(bytes.to_vec())
+        .map_err(|_| thumbnail::thumbnail_error("storyboard sidecar is not valid UTF-8"))?;
+
+    let mut rows = None;
+    let mut cols = None;
+    let mut tile_width = None;
+    let mut tile_height = None;
+    let mut timestamps = Vec::new();
+
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key {
+            "rows" => rows = value.parse().ok(),
+            "cols" => cols = value.parse().ok(),
+            "tile_width" => tile_width = value.parse().ok(),
+            "tile_height" => tile_height = value.parse().ok(),
+            "timestamps" if !value.is_empty() => {
+                timestamps = value
+                    .split(',')
+                    .filter_map(|entry| entry.parse().ok())
+                    .collect();
+            }
+            _ => (),
+        }
+    }
+
+    let (Some(rows), Some(cols), Some(tile_width), Some(tile_height)) =
+        (rows, cols, tile_width, tile_height)
+    else {
+        return Err(Error::new(ErrorKind::Thumbnail, "storyboard-sidecar-invalid"));
+    };
+
+    Ok(StoryboardSidecar {
+        rows,
+        cols,
+        tile_width,
+        tile_height,
+        timestamps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_timestamps_spans_the_full_duration() {
+        let timestamps = sample_timestamps(100.0, 5);
+        assert_eq!(timestamps, vec![0.0, 20.0, 40.0, 60.0, 80.0]);
+    }
+
+    #[test]
+    fn sample_timestamps_clamps_very_short_clips() {
+        // At 0.2s minimum spacing, a 1-second clip can't hold 25 distinct samples.
+        let timestamps = sample_timestamps(1.0, 25);
+        assert_eq!(timestamps.len(), 5);
+    }
+
+    #[test]
+    fn sample_timestamps_always_samples_at_least_one_frame() {
+        let timestamps = sample_timestamps(0.05, 25);
+        assert_eq!(timestamps.len(), 1);
+        assert_eq!(timestamps[0], 0.0);
+    }
+
+    #[test]
+    fn sidecar_roundtrips_through_its_text_encoding() {
+        let sidecar = StoryboardSidecar {
+            rows: 2,
+            cols: 3,
+            tile_width: 160,
+            tile_height: 90,
+            timestamps: vec![0.0, 1.5, 3.0, 4.5, 6.0, 7.5],
+        };
+        let bytes = encode_sidecar(&sidecar);
+        assert_eq!(decode_sidecar(&bytes).unwrap(), sidecar);
+    }
+
+    #[test]
+    fn pack_grid_sizes_the_sheet_to_rows_and_cols() {
+        let tile = image::RgbImage::new(4, 3);
+        let sheet = pack_grid(&[tile.clone(), tile.clone(), tile], 2, 2, 4, 3);
+        assert_eq!(sheet.dimensions(), (8, 6));
+    }
+}
@@ -0,0 +1,235 @@
+//! Thumbnail generation, backing `<repo>/thumbnail`.
+//!
+//! `Repo::import_file` used to leave a `// TODO: Generate thumbnail` in place of this, and assumed
+//! every import was a video. There are now two strategies, dispatched by `media::MediaKind`:
+//! `generate_video` decodes a single frame near the start of a video with `ffmpeg-next`;
+//! `generate_image` just downscales the image itself. Both scale down preserving aspect ratio and
+//! write a JPEG under `{hash[0..2]}/{hash[2..]}.jpg`, mirroring the hash-prefixed layout
+//! `store::LocalFsStore` uses for chunks. Thumbnails are always local files: unlike the chunk
+//! store, there is no pluggable `Store` backend for them yet.
+
+use crate::error::{Error, ErrorKind, Result};
+use ffmpeg_next as ffmpeg;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Longest edge, in pixels, that generated thumbnails are scaled to. Aspect ratio is preserved, so
+/// the other edge may be smaller.
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 320;
+
+/// Fraction into a video's duration that the thumbnail frame is taken from.
+const SEEK_FRACTION: f64 = 0.1;
+
+/// Path a thumbnail for `hash` would live at under `thumbnail_root`.
+pub(crate) fn path_for(thumbnail_root: &Path, hash: &str) -> PathBuf {
+    thumbnail_root
+        .join(&hash[0..2])
+        .join(format!("{}.jpg", &hash[2..]))
+}
+
+/// Generates a thumbnail for the video at `video_path` and writes it under `thumbnail_root`,
+/// scaled so its longest edge is `max_size` pixels.
+///
+/// Returns `Ok(false)` without writing anything if `video_path` has no decodable video stream:
+/// this is not an error, since e.g. an audio-only file can still otherwise import fine. Returns
+/// `Ok(true)` once the thumbnail has been written.
+///
+/// # Errors
+/// - `ErrorKind::Thumbnail` if opening, decoding, scaling, or encoding the frame fails, or if the
+///   thumbnail cannot be written to disk.
+pub fn generate_video<T>(
+    video_path: T,
+    hash: &str,
+    thumbnail_root: &Path,
+    max_size: u32,
+) -> Result<bool>
+where
+    T: AsRef<Path>,
+{
+    let video_path = video_path.as_ref();
+    ffmpeg::init().map_err(thumbnail_error)?;
+
+    let mut input = ffmpeg::format::input(&video_path).map_err(thumbnail_error)?;
+
+    let Some(stream) = input.streams().best(ffmpeg::media::Type::Video) else {
+        return Ok(false);
+    };
+    let video_stream_index = stream.index();
+
+    let decoder_context =
+        ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+            .map_err(thumbnail_error)?;
+    let mut decoder = decoder_context.decoder().video().map_err(thumbnail_error)?;
+
+    // Best-effort seek to roughly 10% into the video; very short or duration-less clips simply
+    // decode from the start instead.
+    let duration = input.duration();
+    if duration > 0 {
+        let target = (duration as f64 * SEEK_FRACTION) as i64;
+        let _ = input.seek(target, ..target);
+    }
+
+    let Some(frame) = decode_first_frame(&mut input, &mut decoder, video_stream_index)? else {
+        return Ok(false);
+    };
+
+    let scaled = scale_frame(&frame, max_size).map_err(thumbnail_error)?;
+    let image = video_frame_to_image(&scaled)?;
+    write_jpeg(&image, &path_for(thumbnail_root, hash))?;
+
+    Ok(true)
+}
+
+/// Generates a thumbnail for the image at `image_path` and writes it under `thumbnail_root`,
+/// scaled so its longest edge is `max_size` pixels.
+///
+/// # Errors
+/// - `ErrorKind::Thumbnail` if `image_path` cannot be decoded, or the thumbnail cannot be written
+///   to disk.
+pub fn generate_image<T>(
+    image_path: T,
+    hash: &str,
+    thumbnail_root: &Path,
+    max_size: u32,
+) -> Result<bool>
+where
+    T: AsRef<Path>,
+{
+    let source = image::open(image_path.as_ref()).map_err(thumbnail_error)?;
+    let (width, height) = bounded_dimensions(source.width(), source.height(), max_size);
+    let scaled = source.resize(width, height, image::imageops::FilterType::Triangle);
+
+    write_jpeg(&scaled.to_rgb8(), &path_for(thumbnail_root, hash))?;
+
+    Ok(true)
+}
+
+/// Reads packets from `input` until the decoder yields the first frame of `video_stream_index`,
+/// or the stream is exhausted. Shared with `storyboard`, which calls this once per sampled
+/// timestamp after seeking.
+pub(crate) fn decode_first_frame(
+    input: &mut ffmpeg::format::context::Input,
+    decoder: &mut ffmpeg::decoder::Video,
+    video_stream_index: usize,
+) -> Result<Option<ffmpeg::util::frame::Video>> {
+    let mut decoded = ffmpeg::util::frame::Video::empty();
+
+    for (stream, packet) in input.packets() {
+        if stream.index() != video_stream_index {
+            continue;
+        }
+        decoder.send_packet(&packet).map_err(thumbnail_error)?;
+        if decoder.receive_frame(&mut decoded).is_ok() {
+            return Ok(Some(decoded));
+        }
+    }
+
+    // Flush: some codecs only emit their first frame once they see EOF.
+    let _ = decoder.send_eof();
+    if decoder.receive_frame(&mut decoded).is_ok() {
+        return Ok(Some(decoded));
+    }
+
+    Ok(None)
+}
+
+/// Scales `frame` to RGB24 with its longest edge bounded by `max_size`, preserving aspect ratio.
+/// Shared with `storyboard`, which scales every sampled frame down to its tile size this way.
+pub(crate) fn scale_frame(
+    frame: &ffmpeg::util::frame::Video,
+    max_size: u32,
+) -> std::result::Result<ffmpeg::util::frame::Video, ffmpeg::Error> {
+    let (width, height) = bounded_dimensions(frame.width(), frame.height(), max_size);
+
+    let mut scaler = ffmpeg::software::scaling::Context::get(
+        frame.format(),
+        frame.width(),
+        frame.height(),
+        ffmpeg::format::Pixel::RGB24,
+        width,
+        height,
+        ffmpeg::software::scaling::Flags::BILINEAR,
+    )?;
+
+    let mut scaled = ffmpeg::util::frame::Video::empty();
+    scaler.run(frame, &mut scaled)?;
+    Ok(scaled)
+}
+
+/// Scales `(width, height)` down so the longest edge is `max_size`, preserving aspect ratio.
+/// Never scales up: frames smaller than `max_size` are kept as-is.
+fn bounded_dimensions(width: u32, height: u32, max_size: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= max_size {
+        return (width, height);
+    }
+    let scale = f64::from(max_size) / f64::from(longest);
+    (
+        ((f64::from(width) * scale).round() as u32).max(1),
+        ((f64::from(height) * scale).round() as u32).max(1),
+    )
+}
+
+/// Unpacks a scaled RGB24 `ffmpeg` frame (which may be row-padded) into a plain `image::RgbImage`.
+/// Shared with `storyboard`, which unpacks every sampled frame this way before tiling them.
+pub(crate) fn video_frame_to_image(frame: &ffmpeg::util::frame::Video) -> Result<image::RgbImage> {
+    let width = frame.width();
+    let height = frame.height();
+    let stride = frame.stride(0);
+    let data = frame.data(0);
+
+    let mut packed = Vec::with_capacity((width * height * 3) as usize);
+    for row in 0..height as usize {
+        let row_start = row * stride;
+        packed.extend_from_slice(&data[row_start..row_start + width as usize * 3]);
+    }
+
+    image::RgbImage::from_raw(width, height, packed)
+        .ok_or_else(|| thumbnail_error("decoded thumbnail frame has an unexpected buffer size"))
+}
+
+/// Writes an RGB image out as a JPEG file, creating its parent directory as needed.
+fn write_jpeg(image: &image::RgbImage, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    image
+        .save_with_format(path, image::ImageFormat::Jpeg)
+        .map_err(thumbnail_error)?;
+
+    Ok(())
+}
+
+pub(crate) fn thumbnail_error(detail: impl std::fmt::Display) -> Error {
+    Error::with_args(
+        ErrorKind::Thumbnail,
+        "thumbnail-error",
+        vec![("detail", detail.to_string())],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_dimensions_keeps_small_frames_unscaled() {
+        assert_eq!(bounded_dimensions(200, 100, 320), (200, 100));
+    }
+
+    #[test]
+    fn bounded_dimensions_scales_longest_edge_down() {
+        assert_eq!(bounded_dimensions(1920, 1080, 320), (320, 180));
+    }
+
+    #[test]
+    fn path_for_uses_hash_prefixed_layout() {
+        let root = Path::new("/repo/thumbnail");
+        assert_eq!(
+            path_for(root, "abcdef123456"),
+            Path::new("/repo/thumbnail/ab/cdef123456.jpg")
+        );
+    }
+}
@@ -1,3 +1,63 @@
+/// Length in hex chars of an item/thumbnail hash (SHA-224, see `Repo::hash`/`Repo::hash_bytes`).
+/// Shared by `archive::validate_member_hash` and `db::validate_item_hash` so the two call sites
+/// hardened against short/non-hex item hashes can't drift apart if the digest ever changes.
+pub const ITEM_HASH_HEX_LEN: usize = 56;
+
+/// Returns whether `hash` is exactly `expected_len` lowercase hex digits — the shape a chunk or
+/// item hash must have before it's safe to slice into a `hash[0..2]`/`hash[2..]` store or
+/// thumbnail path. Hashes that reach such a path-construction call site unchecked (an archive
+/// manifest the archive author controls, or a JSON catalog snapshot someone hand-edited) are an
+/// arbitrary path-traversal/panic primitive otherwise; see `archive::validate_member_hash` and
+/// `db::validate_item_hash`, its two callers.
+pub fn is_lowercase_hex(hash: &str, expected_len: usize) -> bool {
+    hash.len() == expected_len
+        && hash.bytes().all(|b| b.is_ascii_digit() || matches!(b, b'a'..=b'f'))
+}
+
+/// Walks two hash lists, both sorted ascending, and reports every hash present in only one of
+/// them. Unlike `compare_lists`, this collects every mismatch rather than stopping at the first,
+/// which is what both `check_data_integrity` (db vs. store) and `archive::import` (archive vs. an
+/// existing store being merged into) need.
+///
+/// Returns `(missing, unexpected)`: hashes in `expected` but not `actual`, and hashes in `actual`
+/// but not `expected`, respectively.
+pub fn reconcile_sorted_hashes(
+    expected: &[String],
+    actual: &[String],
+) -> (Vec<String>, Vec<String>) {
+    let mut missing = Vec::new();
+    let mut unexpected = Vec::new();
+
+    let mut i = 0;
+    let mut j = 0;
+    while i < expected.len() && j < actual.len() {
+        let expected_hash = &expected[i];
+        let actual_hash = &actual[j];
+        if expected_hash == actual_hash {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        if expected_hash < actual_hash {
+            missing.push(expected_hash.clone());
+            i += 1;
+            continue;
+        }
+        unexpected.push(actual_hash.clone());
+        j += 1;
+    }
+    while i < expected.len() {
+        missing.push(expected[i].clone());
+        i += 1;
+    }
+    while j < actual.len() {
+        unexpected.push(actual[j].clone());
+        j += 1;
+    }
+
+    (missing, unexpected)
+}
+
 #[derive(PartialEq, Debug)]
 pub enum ListCompareResult<T> {
     Missing(T),
@@ -61,6 +121,26 @@ mod tests {
     use super::*;
     use rstest::rstest;
 
+    #[test]
+    fn reconcile_sorted_hashes_finds_both_directions() {
+        let expected = vec!["a".to_string(), "b".to_string(), "d".to_string()];
+        let actual = vec!["a".to_string(), "c".to_string(), "d".to_string()];
+
+        let (missing, unexpected) = reconcile_sorted_hashes(&expected, &actual);
+
+        assert_eq!(missing, vec!["b".to_string()]);
+        assert_eq!(unexpected, vec!["c".to_string()]);
+    }
+
+    #[test]
+    fn reconcile_sorted_hashes_of_identical_lists_is_empty() {
+        let hashes = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(
+            reconcile_sorted_hashes(&hashes, &hashes),
+            (Vec::new(), Vec::new())
+        );
+    }
+
     #[rstest]
     #[case(&[], &[], ListCompareResult::Identical)]
     #[case(&[], &[1], ListCompareResult::Missing(&1))]